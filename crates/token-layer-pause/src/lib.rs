@@ -0,0 +1,60 @@
+// hookswap_amm/crates/token-layer-pause/src/lib.rs
+//! Per-mint pause state owned by token-layer. token-layer writes it and the
+//! transfer hooks read it, so the layout and read helper live here where both
+//! sides can depend on them without depending on each other.
+use anchor_lang::prelude::*;
+
+// token-layer's program id, re-exported by token-layer as its own
+declare_id!("HJ4MosN8hG5qd6WFMKQcBmYVhHuX1EKdPZ1LyaPSdYLA");
+
+/// Seed prefix of the per-mint pause state PDA
+pub const PAUSE_STATE_SEED: &[u8] = b"pause_state";
+
+/// Per-mint emergency stop, read by kyc-hook and whitelist-hook on every transfer
+#[account]
+pub struct PauseState {
+    pub mint: Pubkey,                    // 32 bytes
+    pub pause_authority: Pubkey,         // 32 bytes
+    pub is_paused: bool,                 // 1 byte
+    pub last_reason: String,             // 4 + 100 = 104 bytes
+    pub updated_at: i64,                 // 8 bytes
+    pub bump: u8,                        // 1 byte
+}
+
+impl PauseState {
+    pub const SPACE: usize = 32 + 32 + 1 + 104 + 8 + 1; // 178 bytes
+}
+
+/// Read helper for transfer hooks: returns whether transfers of `mint` are paused.
+///
+/// A pause state that was never initialized counts as not paused, but the
+/// account must still be the canonical `[b"pause_state", mint]` PDA.
+pub fn is_transfer_paused(pause_state: &AccountInfo, mint: &Pubkey) -> Result<bool> {
+    if pause_state.owner != &crate::ID || pause_state.data_is_empty() {
+        let (expected, _) = Pubkey::find_program_address(
+            &[PAUSE_STATE_SEED, mint.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(pause_state.key(), expected, PauseStateError::InvalidPauseState);
+        return Ok(false);
+    }
+
+    let data = pause_state.try_borrow_data()?;
+    let state = PauseState::try_deserialize(&mut &data[..])?;
+    require_keys_eq!(state.mint, *mint, PauseStateError::InvalidPauseState);
+
+    let expected = Pubkey::create_program_address(
+        &[PAUSE_STATE_SEED, mint.as_ref(), &[state.bump]],
+        &crate::ID,
+    ).map_err(|_| PauseStateError::InvalidPauseState)?;
+    require_keys_eq!(pause_state.key(), expected, PauseStateError::InvalidPauseState);
+
+    Ok(state.is_paused)
+}
+
+// Offset keeps these codes clear of the 6000 range of the programs returning them
+#[error_code(offset = 7000)]
+pub enum PauseStateError {
+    #[msg("Invalid pause state account")]
+    InvalidPauseState,
+}
@@ -0,0 +1,450 @@
+//! Concentrated-liquidity math.
+//!
+//! Prices are square roots in Q64.64 fixed point (`sqrt_price_x64`), ticks are
+//! powers of 1.0001 and fee growth is tracked per unit of liquidity in Q64.64.
+//! Products go through a 256-bit `mul_div` so nothing is truncated before the
+//! final division. Like `math`, rounding always favours the pool and the module
+//! has no account access, so it can be used off-chain for quoting.
+
+use crate::math::FEE_RATE_DENOMINATOR;
+use crate::AMMError;
+
+/// 1.0 in Q64.64
+pub const Q64: u128 = 1 << 64;
+
+/// Tick bounds; prices between 2^-64 and 2^64
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+
+/// `sqrt_price_from_tick(MIN_TICK)` and `sqrt_price_from_tick(MAX_TICK)`
+pub const MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+pub const MAX_SQRT_PRICE_X64: u128 = 79_226_673_521_066_979_257_578_248_091;
+
+/// `2^64 / sqrt(1.0001)^(2^i)`, one factor per bit of the absolute tick
+const TICK_RATIOS: [u128; 19] = [
+    0xfffcb933bd6fad37, // 2^64 / sqrt(1.0001)^1
+    0xfff97272373d4132, // 2^64 / sqrt(1.0001)^2
+    0xfff2e50f5f656932, // 2^64 / sqrt(1.0001)^4
+    0xffe5caca7e10e4e6, // 2^64 / sqrt(1.0001)^8
+    0xffcb9843d60f6159, // 2^64 / sqrt(1.0001)^16
+    0xff973b41fa98c081, // 2^64 / sqrt(1.0001)^32
+    0xff2ea16466c96a38, // 2^64 / sqrt(1.0001)^64
+    0xfe5dee046a99a2a8, // 2^64 / sqrt(1.0001)^128
+    0xfcbe86c7900a88ae, // 2^64 / sqrt(1.0001)^256
+    0xf987a7253ac41317, // 2^64 / sqrt(1.0001)^512
+    0xf3392b0822b70005, // 2^64 / sqrt(1.0001)^1024
+    0xe7159475a2c29b74, // 2^64 / sqrt(1.0001)^2048
+    0xd097f3bdfd2022b8, // 2^64 / sqrt(1.0001)^4096
+    0xa9f746462d870fdf, // 2^64 / sqrt(1.0001)^8192
+    0x70d869a156d2a1b8, // 2^64 / sqrt(1.0001)^16384
+    0x31be135f97d08fd9, // 2^64 / sqrt(1.0001)^32768
+    0x09aa508b5b7a84e1, // 2^64 / sqrt(1.0001)^65536
+    0x005d6af8dedb8119, // 2^64 / sqrt(1.0001)^131072
+    0x00002216e584f5fa, // 2^64 / sqrt(1.0001)^262144
+];
+
+/// Result of one step of a swap within a single liquidity range
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next_x64: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+/// 256-bit product of two u128s as (high, low) halves
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & MASK);
+    let (b1, b0) = (b >> 64, b & MASK);
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+    let lo = (p00 & MASK) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// `a * b / denominator` with a 256-bit intermediate
+pub fn mul_div(a: u128, b: u128, denominator: u128, round_up: bool) -> Result<u128, AMMError> {
+    if denominator == 0 {
+        return Err(AMMError::MathOverflow);
+    }
+    let (hi, lo) = full_mul(a, b);
+    if hi >= denominator {
+        return Err(AMMError::MathOverflow);
+    }
+
+    let (quotient, remainder) = if hi == 0 {
+        (lo / denominator, lo % denominator)
+    } else {
+        // Shift-subtract long division; hi < denominator keeps the quotient in u128
+        let mut remainder = hi;
+        let mut quotient = 0u128;
+        for i in (0..128).rev() {
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | ((lo >> i) & 1);
+            quotient <<= 1;
+            if carry == 1 || remainder >= denominator {
+                remainder = remainder.wrapping_sub(denominator);
+                quotient |= 1;
+            }
+        }
+        (quotient, remainder)
+    };
+
+    if round_up && remainder > 0 {
+        quotient.checked_add(1).ok_or(AMMError::MathOverflow)
+    } else {
+        Ok(quotient)
+    }
+}
+
+/// `sqrt(1.0001^tick)` in Q64.64
+pub fn sqrt_price_from_tick(tick: i32) -> Result<u128, AMMError> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(AMMError::InvalidTickRange);
+    }
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = Q64;
+    for (bit, factor) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+    if tick > 0 {
+        ratio = u128::MAX / ratio;
+    }
+    Ok(ratio)
+}
+
+/// Greatest tick whose sqrt price is at or below `sqrt_price_x64`
+pub fn tick_from_sqrt_price(sqrt_price_x64: u128) -> Result<i32, AMMError> {
+    if !(MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64) {
+        return Err(AMMError::InvalidSqrtPrice);
+    }
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_from_tick(mid)? <= sqrt_price_x64 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+/// Token A for `liquidity` between two sqrt prices: `L * (upper - lower) / (upper * lower)`
+pub fn amount_a_delta(
+    sqrt_price_a_x64: u128,
+    sqrt_price_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128, AMMError> {
+    let (lower, upper) = sorted(sqrt_price_a_x64, sqrt_price_b_x64);
+    if lower == 0 {
+        return Err(AMMError::InvalidSqrtPrice);
+    }
+    let per_upper = mul_div(liquidity, upper - lower, upper, round_up)?;
+    mul_div(per_upper, Q64, lower, round_up)
+}
+
+/// Token B for `liquidity` between two sqrt prices: `L * (upper - lower)`
+pub fn amount_b_delta(
+    sqrt_price_a_x64: u128,
+    sqrt_price_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128, AMMError> {
+    let (lower, upper) = sorted(sqrt_price_a_x64, sqrt_price_b_x64);
+    mul_div(liquidity, upper - lower, Q64, round_up)
+}
+
+fn sorted(a: u128, b: u128) -> (u128, u128) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Token amounts backing `liquidity` in `[tick_lower, tick_upper)` at the current price
+pub fn amounts_for_liquidity(
+    sqrt_price_x64: u128,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64), AMMError> {
+    let sqrt_price_lower = sqrt_price_from_tick(tick_lower)?;
+    let sqrt_price_upper = sqrt_price_from_tick(tick_upper)?;
+
+    let (amount_a, amount_b) = if sqrt_price_x64 <= sqrt_price_lower {
+        // Below the range: all token A
+        (amount_a_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?, 0)
+    } else if sqrt_price_x64 >= sqrt_price_upper {
+        // Above the range: all token B
+        (0, amount_b_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?)
+    } else {
+        (
+            amount_a_delta(sqrt_price_x64, sqrt_price_upper, liquidity, round_up)?,
+            amount_b_delta(sqrt_price_lower, sqrt_price_x64, liquidity, round_up)?,
+        )
+    };
+
+    Ok((
+        u64::try_from(amount_a).map_err(|_| AMMError::MathOverflow)?,
+        u64::try_from(amount_b).map_err(|_| AMMError::MathOverflow)?,
+    ))
+}
+
+/// Sqrt price after `amount` of token A enters (price falls), rounded up
+pub fn next_sqrt_price_from_amount_a(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount: u64,
+) -> Result<u128, AMMError> {
+    if amount == 0 {
+        return Ok(sqrt_price_x64);
+    }
+    // sqrt_p' = L * sqrt_p / (L + amount * sqrt_p)
+    let product = mul_div(amount as u128, sqrt_price_x64, Q64, false)?;
+    let denominator = liquidity.checked_add(product).ok_or(AMMError::MathOverflow)?;
+    mul_div(liquidity, sqrt_price_x64, denominator, true)
+}
+
+/// Sqrt price after `amount` of token B enters (price rises), rounded down
+pub fn next_sqrt_price_from_amount_b(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount: u64,
+) -> Result<u128, AMMError> {
+    // sqrt_p' = sqrt_p + amount / L
+    let delta = mul_div(amount as u128, Q64, liquidity, false)?;
+    sqrt_price_x64.checked_add(delta).ok_or(AMMError::MathOverflow)
+}
+
+/// Exact-input swap within one range, from the current price towards `sqrt_price_target_x64`.
+///
+/// The fee comes off the input first; the step either reaches the target with part of
+/// `amount_remaining`, or spends all of it short of the target.
+pub fn compute_swap_step(
+    sqrt_price_current_x64: u128,
+    sqrt_price_target_x64: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee_rate: u64,
+) -> Result<SwapStep, AMMError> {
+    if fee_rate >= FEE_RATE_DENOMINATOR {
+        return Err(AMMError::InvalidFeeRate);
+    }
+    let a_to_b = sqrt_price_target_x64 <= sqrt_price_current_x64;
+    let amount_remaining_less_fee = (amount_remaining as u128
+        * (FEE_RATE_DENOMINATOR - fee_rate) as u128
+        / FEE_RATE_DENOMINATOR as u128) as u64;
+
+    let amount_in_to_target = if a_to_b {
+        amount_a_delta(sqrt_price_target_x64, sqrt_price_current_x64, liquidity, true)
+    } else {
+        amount_b_delta(sqrt_price_current_x64, sqrt_price_target_x64, liquidity, true)
+    };
+
+    let reaches_target = matches!(amount_in_to_target, Ok(amount) if amount <= amount_remaining_less_fee as u128);
+    let sqrt_price_next_x64 = if reaches_target {
+        sqrt_price_target_x64
+    } else if a_to_b {
+        next_sqrt_price_from_amount_a(sqrt_price_current_x64, liquidity, amount_remaining_less_fee)?
+    } else {
+        next_sqrt_price_from_amount_b(sqrt_price_current_x64, liquidity, amount_remaining_less_fee)?
+    };
+
+    let (amount_in, amount_out) = if a_to_b {
+        (
+            amount_a_delta(sqrt_price_next_x64, sqrt_price_current_x64, liquidity, true)?,
+            amount_b_delta(sqrt_price_next_x64, sqrt_price_current_x64, liquidity, false)?,
+        )
+    } else {
+        (
+            amount_b_delta(sqrt_price_current_x64, sqrt_price_next_x64, liquidity, true)?,
+            amount_a_delta(sqrt_price_current_x64, sqrt_price_next_x64, liquidity, false)?,
+        )
+    };
+    let amount_in = u64::try_from(amount_in)
+        .map_err(|_| AMMError::MathOverflow)?
+        .min(amount_remaining_less_fee);
+    let amount_out = u64::try_from(amount_out).map_err(|_| AMMError::MathOverflow)?;
+
+    let fee_amount = if reaches_target {
+        // Fee on the input actually used, rounded up
+        u64::try_from(
+            (amount_in as u128 * fee_rate as u128)
+                .div_ceil((FEE_RATE_DENOMINATOR - fee_rate) as u128),
+        )
+        .map_err(|_| AMMError::MathOverflow)?
+    } else {
+        // The whole remainder is consumed; whatever the curve did not take is fee
+        amount_remaining - amount_in
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next_x64,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// Q64.64 fee growth per unit of liquidity for `fee_amount`
+pub fn fee_growth_delta(fee_amount: u64, liquidity: u128) -> Result<u128, AMMError> {
+    if liquidity == 0 {
+        return Ok(0);
+    }
+    mul_div(fee_amount as u128, Q64, liquidity, false)
+}
+
+/// Fee growth inside `[tick_lower, tick_upper)` from the global and per-tick outside values.
+///
+/// Growth values wrap on overflow; only their differences are meaningful.
+pub fn fee_growth_inside(
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_current: i32,
+    fee_growth_global_x64: u128,
+    lower_outside_x64: u128,
+    upper_outside_x64: u128,
+) -> u128 {
+    let below = if tick_current >= tick_lower {
+        lower_outside_x64
+    } else {
+        fee_growth_global_x64.wrapping_sub(lower_outside_x64)
+    };
+    let above = if tick_current < tick_upper {
+        upper_outside_x64
+    } else {
+        fee_growth_global_x64.wrapping_sub(upper_outside_x64)
+    };
+    fee_growth_global_x64.wrapping_sub(below).wrapping_sub(above)
+}
+
+/// Fees earned by `liquidity` over a fee growth difference, rounded down
+pub fn fees_earned(
+    fee_growth_inside_x64: u128,
+    fee_growth_inside_last_x64: u128,
+    liquidity: u128,
+) -> Result<u64, AMMError> {
+    let growth = fee_growth_inside_x64.wrapping_sub(fee_growth_inside_last_x64);
+    let fees = mul_div(growth, liquidity, Q64, false)?;
+    u64::try_from(fees).map_err(|_| AMMError::MathOverflow)
+}
+
+/// Apply a signed liquidity change
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128, AMMError> {
+    if delta >= 0 {
+        liquidity.checked_add(delta as u128)
+    } else {
+        liquidity.checked_sub(delta.unsigned_abs())
+    }
+    .ok_or(AMMError::MathOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_price_at_tick_bounds_and_zero() {
+        assert_eq!(sqrt_price_from_tick(0).unwrap(), Q64);
+        assert_eq!(sqrt_price_from_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_from_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE_X64);
+        assert!(matches!(sqrt_price_from_tick(MIN_TICK - 1), Err(AMMError::InvalidTickRange)));
+        assert!(matches!(sqrt_price_from_tick(MAX_TICK + 1), Err(AMMError::InvalidTickRange)));
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_bounds_and_zero() {
+        assert_eq!(tick_from_sqrt_price(Q64).unwrap(), 0);
+        assert_eq!(tick_from_sqrt_price(Q64 - 1).unwrap(), -1);
+        assert_eq!(tick_from_sqrt_price(MIN_SQRT_PRICE_X64).unwrap(), MIN_TICK);
+        assert_eq!(tick_from_sqrt_price(MAX_SQRT_PRICE_X64).unwrap(), MAX_TICK);
+        assert!(matches!(tick_from_sqrt_price(MIN_SQRT_PRICE_X64 - 1), Err(AMMError::InvalidSqrtPrice)));
+        assert!(matches!(tick_from_sqrt_price(MAX_SQRT_PRICE_X64 + 1), Err(AMMError::InvalidSqrtPrice)));
+    }
+
+    #[test]
+    fn tick_round_trips_and_price_is_monotonic() {
+        let ticks = [MIN_TICK, MIN_TICK + 1, -100_000, -887, -1, 0, 1, 64, 12_345, MAX_TICK - 1, MAX_TICK];
+        for tick in ticks {
+            let sqrt_price = sqrt_price_from_tick(tick).unwrap();
+            assert_eq!(tick_from_sqrt_price(sqrt_price).unwrap(), tick);
+            if tick < MAX_TICK {
+                let next = sqrt_price_from_tick(tick + 1).unwrap();
+                assert!(next > sqrt_price);
+                // Prices between two ticks belong to the lower one
+                assert_eq!(tick_from_sqrt_price(next - 1).unwrap(), tick);
+            }
+        }
+    }
+
+    #[test]
+    fn amounts_for_liquidity_round_in_favour_of_the_pool() {
+        let liquidity = 1_000_000_007;
+        let current = sqrt_price_from_tick(5).unwrap();
+        for (lower, upper) in [(-60, 60), (10, 70), (-70, -10)] {
+            let (down_a, down_b) = amounts_for_liquidity(current, lower, upper, liquidity, false).unwrap();
+            let (up_a, up_b) = amounts_for_liquidity(current, lower, upper, liquidity, true).unwrap();
+            // Deposits round up, withdrawals down; token A rounds twice (per upper, then per lower)
+            assert!(up_a >= down_a && up_a - down_a <= 2);
+            assert!(up_b >= down_b && up_b - down_b <= 1);
+        }
+
+        // Below the range only token A is needed, above it only token B
+        let (a, b) = amounts_for_liquidity(current, 10, 70, liquidity, true).unwrap();
+        assert!(a > 0 && b == 0);
+        let (a, b) = amounts_for_liquidity(current, -70, -10, liquidity, true).unwrap();
+        assert!(a == 0 && b > 0);
+    }
+
+    #[test]
+    fn fee_growth_inside_below_inside_and_above_the_range() {
+        let (tick_lower, tick_upper) = (-10, 10);
+        let global = 1_000;
+
+        // Inside: outside growth of both ticks is what happened beyond them
+        assert_eq!(fee_growth_inside(tick_lower, tick_upper, 0, global, 100, 200), 700);
+
+        // Below: the lower tick's outside growth is everything above it
+        assert_eq!(fee_growth_inside(tick_lower, tick_upper, -20, global, 300, 200), 100);
+
+        // Above: the upper tick's outside growth is everything below it
+        assert_eq!(fee_growth_inside(tick_lower, tick_upper, 20, global, 100, 400), 300);
+
+        // The lower bound is inclusive, the upper exclusive
+        assert_eq!(fee_growth_inside(tick_lower, tick_upper, tick_lower, global, 100, 200), 700);
+        assert_eq!(fee_growth_inside(tick_lower, tick_upper, tick_upper, global, 100, 400), 300);
+
+        // Accumulators wrap
+        let wrapped = fee_growth_inside(tick_lower, tick_upper, 0, 50, u128::MAX - 49, 0);
+        assert_eq!(wrapped, 100);
+    }
+
+    #[test]
+    fn swap_step_never_spends_more_than_remaining() {
+        let liquidity = 10_000_000_000;
+        let current = sqrt_price_from_tick(0).unwrap();
+        let target = sqrt_price_from_tick(-100).unwrap();
+
+        // Small amount: stops short of the target and the whole amount is used
+        let step = compute_swap_step(current, target, liquidity, 1_000, 30).unwrap();
+        assert!(step.sqrt_price_next_x64 < current && step.sqrt_price_next_x64 > target);
+        assert_eq!(step.amount_in + step.fee_amount, 1_000);
+
+        // Large amount: reaches the target and leaves the rest
+        let step = compute_swap_step(current, target, liquidity, u64::MAX / 2, 30).unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert!(step.amount_in + step.fee_amount < u64::MAX / 2);
+        assert!(step.fee_amount as u128 * FEE_RATE_DENOMINATOR as u128 >= step.amount_in as u128 * 30);
+    }
+}
@@ -1,16 +1,27 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{
-    program::invoke,
-    program::invoke_signed,
-    program_pack::Pack,
-};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::Discriminator;
 use anchor_spl::token_2022::Token2022;
-use anchor_spl::token_interface::{Mint, TokenAccount};
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount};
 use spl_token_2022::{
-    instruction::transfer_checked,
+    onchain::invoke_transfer_checked,
     state::Mint as Token2022Mint,
-    extension::{StateWithExtensions, BaseStateWithExtensions},
+    extension::{
+        transfer_fee::TransferFeeConfig, transfer_hook, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+};
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::ExecuteInstruction,
 };
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_type_length_value::state::TlvStateBorrowed;
+
+pub mod math;
+pub mod stable_math;
+pub mod clmm_math;
+pub mod weighted_math;
 
 declare_id!("EJCk9aNdKk21Mr3C33aYtnnuBe2vKxVm9eS3TjLWUHuB");
 
@@ -23,9 +34,20 @@ pub mod hookswap_amm {
         ctx: Context<InitializeAMM>,
         fee_rate: u64, // basis points (e.g. 30 = 0.3%)
     ) -> Result<()> {
+        require!(fee_rate <= math::FEE_RATE_DENOMINATOR, AMMError::InvalidFeeRate);
+        
         let amm_config = &mut ctx.accounts.amm_config;
         amm_config.authority = ctx.accounts.authority.key();
+        amm_config.pending_authority = None;
+        amm_config.fee_manager = ctx.accounts.authority.key();
+        amm_config.pauser = ctx.accounts.authority.key();
+        amm_config.registry_manager = ctx.accounts.authority.key();
+        amm_config.paused = false;
+        amm_config.paused_hook_programs = vec![];
         amm_config.fee_rate = fee_rate;
+        amm_config.fee_tiers = vec![fee_rate]; // default tier
+        amm_config.protocol_fee_rate = 0;
+        amm_config.treasury = ctx.accounts.authority.key();
         amm_config.total_pools = 0;
         amm_config.created_at = Clock::get()?.unix_timestamp;
         amm_config.hook_registry = None; // Will be set later
@@ -34,15 +56,295 @@ pub mod hookswap_amm {
         Ok(())
     }
 
-    /// Set hook registry for AMM
+    /// Set hook registry for AMM (registry manager)
     pub fn set_hook_registry(
         ctx: Context<SetHookRegistry>,
         hook_registry: Pubkey,
     ) -> Result<()> {
         let amm_config = &mut ctx.accounts.amm_config;
+        let old_hook_registry = amm_config.hook_registry;
         amm_config.hook_registry = Some(hook_registry);
         
         msg!("🔗 Hook registry set: {}", hook_registry);
+        
+        emit!(HookRegistryUpdatedEvent {
+            old_hook_registry,
+            new_hook_registry: hook_registry,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Hand an admin role to another account (owner)
+    pub fn set_admin_role(
+        ctx: Context<UpdateAMMConfig>,
+        role: AdminRole,
+        account: Pubkey,
+    ) -> Result<()> {
+        let amm_config = &mut ctx.accounts.amm_config;
+        let slot = match role {
+            AdminRole::FeeManager => &mut amm_config.fee_manager,
+            AdminRole::Pauser => &mut amm_config.pauser,
+            AdminRole::RegistryManager => &mut amm_config.registry_manager,
+        };
+        let old_account = *slot;
+        *slot = account;
+        
+        msg!("👤 {:?} role: {} -> {}", role, old_account, account);
+        
+        emit!(AdminRoleUpdatedEvent {
+            role,
+            old_account,
+            new_account: account,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Propose a new owner; takes effect once they call `accept_ownership`
+    pub fn transfer_ownership(
+        ctx: Context<UpdateAMMConfig>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let amm_config = &mut ctx.accounts.amm_config;
+        amm_config.pending_authority = Some(new_authority);
+        
+        msg!("📨 Ownership transfer proposed: {} -> {}", amm_config.authority, new_authority);
+        
+        emit!(OwnershipTransferStartedEvent {
+            authority: amm_config.authority,
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Complete a two-step ownership transfer as the proposed owner
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        let amm_config = &mut ctx.accounts.amm_config;
+        let old_authority = amm_config.authority;
+        amm_config.authority = ctx.accounts.new_authority.key();
+        amm_config.pending_authority = None;
+        
+        msg!("👑 Ownership transferred: {} -> {}", old_authority, amm_config.authority);
+        
+        emit!(OwnershipTransferredEvent {
+            old_authority,
+            new_authority: amm_config.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Pause or resume swaps and deposits in every pool (pauser)
+    pub fn set_paused(
+        ctx: Context<UpdatePauseConfig>,
+        paused: bool,
+    ) -> Result<()> {
+        ctx.accounts.amm_config.paused = paused;
+        
+        msg!("⏸️ AMM paused: {}", paused);
+        
+        emit!(AMMPauseUpdatedEvent {
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Stop trading and deposits in every pool holding a mint hooked to `hook_program` (pauser)
+    pub fn pause_hook_program(
+        ctx: Context<UpdatePauseConfig>,
+        hook_program: Pubkey,
+    ) -> Result<()> {
+        let amm_config = &mut ctx.accounts.amm_config;
+        require!(
+            !amm_config.paused_hook_programs.contains(&hook_program),
+            AMMError::HookProgramAlreadyPaused
+        );
+        require!(
+            amm_config.paused_hook_programs.len() < MAX_PAUSED_HOOK_PROGRAMS,
+            AMMError::TooManyPausedHookPrograms
+        );
+        
+        amm_config.paused_hook_programs.push(hook_program);
+        
+        msg!("⛔ Hook program paused: {}", hook_program);
+        
+        emit!(HookProgramPauseUpdatedEvent {
+            hook_program,
+            paused: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Lift a hook program pause (pauser)
+    pub fn unpause_hook_program(
+        ctx: Context<UpdatePauseConfig>,
+        hook_program: Pubkey,
+    ) -> Result<()> {
+        let amm_config = &mut ctx.accounts.amm_config;
+        require!(
+            amm_config.paused_hook_programs.contains(&hook_program),
+            AMMError::HookProgramNotPaused
+        );
+        
+        amm_config.paused_hook_programs.retain(|program| *program != hook_program);
+        
+        msg!("✅ Hook program unpaused: {}", hook_program);
+        
+        emit!(HookProgramPauseUpdatedEvent {
+            hook_program,
+            paused: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Pause a pool, or put it in emergency mode so LPs can `emergency_withdraw` (pauser).
+    /// Either state blocks swaps and deposits; `remove_liquidity` keeps working.
+    pub fn set_pool_status(
+        ctx: Context<SetPoolStatus>,
+        paused: bool,
+        emergency_mode: bool,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.paused = paused;
+        pool.emergency_mode = emergency_mode;
+        
+        msg!("🚦 Pool {} status: paused={}, emergency={}", pool.key(), paused, emergency_mode);
+        
+        emit!(PoolStatusUpdatedEvent {
+            pool: pool.key(),
+            paused,
+            emergency_mode,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Pause a concentrated pool, or put it in emergency mode so positions can
+    /// use `emergency_decrease_liquidity` (pauser)
+    pub fn set_cl_pool_status(
+        ctx: Context<SetClPoolStatus>,
+        paused: bool,
+        emergency_mode: bool,
+    ) -> Result<()> {
+        let cl_pool = &mut ctx.accounts.cl_pool;
+        cl_pool.paused = paused;
+        cl_pool.emergency_mode = emergency_mode;
+        
+        msg!("🚦 Concentrated pool {} status: paused={}, emergency={}", cl_pool.key(), paused, emergency_mode);
+        
+        emit!(PoolStatusUpdatedEvent {
+            pool: cl_pool.key(),
+            paused,
+            emergency_mode,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Pause a weighted pool, or put it in emergency mode so LPs can use
+    /// `emergency_exit_weighted_pool` (pauser)
+    pub fn set_weighted_pool_status(
+        ctx: Context<SetWeightedPoolStatus>,
+        paused: bool,
+        emergency_mode: bool,
+    ) -> Result<()> {
+        let weighted_pool = &mut ctx.accounts.weighted_pool;
+        weighted_pool.paused = paused;
+        weighted_pool.emergency_mode = emergency_mode;
+        
+        msg!("🚦 Weighted pool {} status: paused={}, emergency={}", weighted_pool.key(), paused, emergency_mode);
+        
+        emit!(PoolStatusUpdatedEvent {
+            pool: weighted_pool.key(),
+            paused,
+            emergency_mode,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Add a fee tier pools can be created with (fee manager)
+    pub fn add_fee_tier(
+        ctx: Context<UpdateFeeConfig>,
+        fee_rate: u64, // basis points
+    ) -> Result<()> {
+        require!(fee_rate <= math::FEE_RATE_DENOMINATOR, AMMError::InvalidFeeRate);
+        
+        let amm_config = &mut ctx.accounts.amm_config;
+        require!(!amm_config.fee_tiers.contains(&fee_rate), AMMError::FeeTierAlreadyExists);
+        require!(amm_config.fee_tiers.len() < MAX_FEE_TIERS, AMMError::TooManyFeeTiers);
+        
+        amm_config.fee_tiers.push(fee_rate);
+        
+        msg!("➕ Fee tier added: {}bp", fee_rate);
+        
+        emit!(FeeTierAddedEvent {
+            fee_rate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Remove a fee tier (existing pools on that tier keep working; fee manager)
+    pub fn remove_fee_tier(
+        ctx: Context<UpdateFeeConfig>,
+        fee_rate: u64,
+    ) -> Result<()> {
+        let amm_config = &mut ctx.accounts.amm_config;
+        require!(amm_config.fee_tiers.contains(&fee_rate), AMMError::InvalidFeeTier);
+        
+        amm_config.fee_tiers.retain(|&tier| tier != fee_rate);
+        
+        msg!("➖ Fee tier removed: {}bp", fee_rate);
+        
+        emit!(FeeTierRemovedEvent {
+            fee_rate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Set the protocol's share of swap fees and the wallet that collects it (fee manager)
+    pub fn set_protocol_fee(
+        ctx: Context<UpdateFeeConfig>,
+        protocol_fee_rate: u64, // basis points of the swap fee (e.g. 2000 = 20%)
+        treasury: Pubkey,
+    ) -> Result<()> {
+        require!(protocol_fee_rate <= math::FEE_RATE_DENOMINATOR, AMMError::InvalidFeeRate);
+        
+        let amm_config = &mut ctx.accounts.amm_config;
+        let old_protocol_fee_rate = amm_config.protocol_fee_rate;
+        let old_treasury = amm_config.treasury;
+        amm_config.protocol_fee_rate = protocol_fee_rate;
+        amm_config.treasury = treasury;
+        
+        msg!("🏦 Protocol fee: {}bp of swap fees to {}", protocol_fee_rate, treasury);
+        
+        emit!(ProtocolFeeUpdatedEvent {
+            old_protocol_fee_rate,
+            new_protocol_fee_rate: protocol_fee_rate,
+            old_treasury,
+            new_treasury: treasury,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         Ok(())
     }
 
@@ -50,7 +352,27 @@ pub mod hookswap_amm {
     pub fn create_pool(
         ctx: Context<CreatePool>,
         initial_price: u64, // Price ratio * 10^9
+        fee_rate: u64,      // one of AMMConfig.fee_tiers, part of the pool seeds
+        pool_type: PoolType,
+        amp: u64,           // StableSwap amplification, ignored for constant product
     ) -> Result<()> {
+        require!(
+            ctx.accounts.amm_config.fee_tiers.contains(&fee_rate),
+            AMMError::InvalidFeeTier
+        );
+        
+        // StableSwap assumes both sides are worth about one unit each
+        if pool_type == PoolType::StableSwap {
+            require!(
+                (stable_math::MIN_AMP..=stable_math::MAX_AMP).contains(&amp),
+                AMMError::InvalidAmplification
+            );
+            require!(
+                get_mint_decimals(&ctx.accounts.token_a_mint)? == get_mint_decimals(&ctx.accounts.token_b_mint)?,
+                AMMError::StableDecimalsMismatch
+            );
+        }
+        
         let pool = &mut ctx.accounts.pool;
         
         // Verify mints are Token-2022
@@ -63,19 +385,57 @@ pub mod hookswap_amm {
             AMMError::InvalidTokenProgram
         );
         
+        // Hooked mints must use a registry-approved hook program
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        validate_transfer_hooks_real(&ctx.accounts.token_a_mint, &ctx.accounts.amm_config, hook_registry)?;
+        validate_transfer_hooks_real(&ctx.accounts.token_b_mint, &ctx.accounts.amm_config, hook_registry)?;
+        
+        // Record the transfer-affecting extensions of each side
+        let token_a_info = read_mint_transfer_info(&ctx.accounts.token_a_mint)?;
+        let token_b_info = read_mint_transfer_info(&ctx.accounts.token_b_mint)?;
+        require!(
+            token_a_info.flags & MINT_FLAG_NON_TRANSFERABLE == 0
+                && token_b_info.flags & MINT_FLAG_NON_TRANSFERABLE == 0,
+            AMMError::UnsupportedMintExtension
+        );
+        // A frozen DefaultAccountState would leave the new vaults unusable
+        require!(
+            !ctx.accounts.token_a_vault.is_frozen() && !ctx.accounts.token_b_vault.is_frozen(),
+            AMMError::UnsupportedMintExtension
+        );
+        
         pool.token_a_mint = ctx.accounts.token_a_mint.key();
         pool.token_b_mint = ctx.accounts.token_b_mint.key();
         pool.creator = ctx.accounts.creator.key();
         pool.created_at = Clock::get()?.unix_timestamp;
-        pool.fee_rate = ctx.accounts.amm_config.fee_rate;
+        pool.fee_rate = fee_rate;
         pool.current_price = initial_price;
         pool.total_liquidity_a = 0;
         pool.total_liquidity_b = 0;
         pool.lp_token_supply = 0;
-        pool.hook_enabled = check_mint_has_hooks(&ctx.accounts.token_a_mint)? || 
-                           check_mint_has_hooks(&ctx.accounts.token_b_mint)?;
+        pool.protocol_fees_a = 0;
+        pool.protocol_fees_b = 0;
+        pool.price_a_cumulative = 0;
+        pool.price_b_cumulative = 0;
+        pool.last_update_timestamp = pool.created_at;
+        pool.lp_mint = ctx.accounts.lp_mint.key();
+        pool.token_a_vault = ctx.accounts.token_a_vault.key();
+        pool.token_b_vault = ctx.accounts.token_b_vault.key();
+        pool.token_a_hook_program = token_a_info.hook_program_id;
+        pool.token_b_hook_program = token_b_info.hook_program_id;
+        pool.token_a_flags = token_a_info.flags;
+        pool.token_b_flags = token_b_info.flags;
+        pool.hook_enabled = pool.token_a_hook_program.is_some() || pool.token_b_hook_program.is_some();
         pool.token_program_id = spl_token_2022::id();
         pool.bump = ctx.bumps.pool;
+        pool.pool_type = pool_type;
+        let amp = if pool_type == PoolType::StableSwap { amp } else { 0 };
+        pool.amp_initial = amp;
+        pool.amp_target = amp;
+        pool.amp_ramp_start = pool.created_at;
+        pool.amp_ramp_end = pool.created_at;
+        pool.paused = false;
+        pool.emergency_mode = false;
         
         // Update AMM config
         let amm_config = &mut ctx.accounts.amm_config;
@@ -83,512 +443,4564 @@ pub mod hookswap_amm {
         
         msg!("🏊 Pool created for {}/{}", 
              pool.token_a_mint, pool.token_b_mint);
+        msg!("🎟️ LP mint: {}", pool.lp_mint);
+        msg!("🏦 Vaults: A={}, B={}", pool.token_a_vault, pool.token_b_vault);
         msg!("💰 Initial price: {}", initial_price);
-        msg!("🔗 Hook validation: {}", pool.hook_enabled);
+        msg!("💸 Fee tier: {}bp", fee_rate);
+        msg!("📈 Curve: {:?} (amp: {})", pool.pool_type, pool.amp_target);
+        msg!("🔗 Hooks: A={:?}, B={:?}", pool.token_a_hook_program, pool.token_b_hook_program);
+        msg!("🏷️ Extension flags: A={:#04x}, B={:#04x}", pool.token_a_flags, pool.token_b_flags);
         Ok(())
     }
 
     /// Add REAL liquidity to pool
-    pub fn add_liquidity(
-        ctx: Context<AddLiquidity>,
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddLiquidity<'info>>,
         amount_a: u64,
         amount_b: u64,
         min_lp_tokens: u64,
     ) -> Result<u64> {
+        ctx.accounts.amm_config.require_not_paused()?;
         let pool = &mut ctx.accounts.pool;
+        pool.require_not_paused()?;
         
         msg!("💧 Adding REAL liquidity: {} A, {} B", amount_a, amount_b);
         
-        // Validate hook requirements if enabled
-        if pool.hook_enabled {
-            validate_transfer_hooks_real(&ctx.accounts.token_a_mint)?;
-            validate_transfer_hooks_real(&ctx.accounts.token_b_mint)?;
-        }
+        // Validate the mints' current hooks (a mint authority can change its hook program)
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        let hook_a = validate_transfer_hooks_real(&ctx.accounts.token_a_mint, &ctx.accounts.amm_config, hook_registry)?;
+        let hook_b = validate_transfer_hooks_real(&ctx.accounts.token_b_mint, &ctx.accounts.amm_config, hook_registry)?;
+        pool.refresh_hook_programs(hook_a, hook_b);
 
         // Get decimals from mint accounts
         let token_a_decimals = get_mint_decimals(&ctx.accounts.token_a_mint)?;
         let token_b_decimals = get_mint_decimals(&ctx.accounts.token_b_mint)?;
         
+        // Hook accounts in remaining_accounts: token A group, then token B group
+        let mut remaining = ctx.remaining_accounts;
+        let hook_accounts_a = take_hook_accounts(&ctx.accounts.token_a_mint, &mut remaining)?;
+        let hook_accounts_b = take_hook_accounts(&ctx.accounts.token_b_mint, &mut remaining)?;
+        
         // REAL Token-2022 transfers
         // Transfer Token A from user to pool
-        let transfer_a_ix = transfer_checked(
-            &spl_token_2022::id(),
-            &ctx.accounts.user_token_a.key(),
-            &ctx.accounts.token_a_mint.key(),
-            &ctx.accounts.pool_token_a.key(),
-            &ctx.accounts.user.key(),
-            &[],
+        transfer_checked_with_hooks(
+            ctx.accounts.user_token_a.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.pool_token_a.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            hook_accounts_a,
             amount_a,
             token_a_decimals,
-        )?;
-
-        invoke(
-            &transfer_a_ix,
-            &[
-                ctx.accounts.user_token_a.to_account_info(),
-                ctx.accounts.token_a_mint.to_account_info(),
-                ctx.accounts.pool_token_a.to_account_info(),
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.token_2022_program.to_account_info(),
-            ],
+            &[],
         )?;
 
         // Transfer Token B from user to pool
-        let transfer_b_ix = transfer_checked(
-            &spl_token_2022::id(),
-            &ctx.accounts.user_token_b.key(),
-            &ctx.accounts.token_b_mint.key(),
-            &ctx.accounts.pool_token_b.key(),
-            &ctx.accounts.user.key(),
-            &[],
+        transfer_checked_with_hooks(
+            ctx.accounts.user_token_b.to_account_info(),
+            ctx.accounts.token_b_mint.to_account_info(),
+            ctx.accounts.pool_token_b.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            hook_accounts_b,
             amount_b,
             token_b_decimals,
-        )?;
-
-        invoke(
-            &transfer_b_ix,
-            &[
-                ctx.accounts.user_token_b.to_account_info(),
-                ctx.accounts.token_b_mint.to_account_info(),
-                ctx.accounts.pool_token_b.to_account_info(),
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.token_2022_program.to_account_info(),
-            ],
+            &[],
         )?;
         
+        // Transfer fees are withheld in the vaults; credit only what arrived
+        let received_a = if pool.token_a_has_transfer_fee() {
+            amount_a.checked_sub(get_transfer_fee(&ctx.accounts.token_a_mint, amount_a)?)
+                .ok_or(AMMError::MathOverflow)?
+        } else {
+            amount_a
+        };
+        let received_b = if pool.token_b_has_transfer_fee() {
+            amount_b.checked_sub(get_transfer_fee(&ctx.accounts.token_b_mint, amount_b)?)
+                .ok_or(AMMError::MathOverflow)?
+        } else {
+            amount_b
+        };
+        let (amount_a, amount_b) = (received_a, received_b);
+        
         // Calculate LP tokens to mint
-        let lp_tokens = if pool.lp_token_supply == 0 {
-            // Initial liquidity - geometric mean minus minimum liquidity
-            let initial_lp = ((amount_a as f64 * amount_b as f64).sqrt() as u64)
-                .checked_sub(1000) // Lock minimum liquidity
-                .unwrap_or(0);
-            initial_lp
-        } else {
-            // Proportional liquidity based on existing pool
-            let lp_from_a = amount_a.checked_mul(pool.lp_token_supply)
-                .unwrap().checked_div(pool.total_liquidity_a).unwrap();
-            let lp_from_b = amount_b.checked_mul(pool.lp_token_supply)
-                .unwrap().checked_div(pool.total_liquidity_b).unwrap();
-            
-            // Take minimum to maintain ratio
-            lp_from_a.min(lp_from_b)
+        let is_initial_deposit = pool.lp_token_supply == 0;
+        let lp_tokens = match pool.pool_type {
+            PoolType::ConstantProduct if is_initial_deposit => {
+                // Initial liquidity - geometric mean minus minimum liquidity
+                math::initial_lp_tokens(amount_a, amount_b)?
+            }
+            PoolType::ConstantProduct => {
+                // Proportional liquidity based on existing pool
+                math::proportional_lp_tokens(
+                    amount_a,
+                    amount_b,
+                    pool.total_liquidity_a,
+                    pool.total_liquidity_b,
+                    pool.lp_token_supply,
+                )?
+            }
+            PoolType::StableSwap => {
+                // Share of the invariant's growth, less the imbalance fee
+                stable_math::deposit_lp_tokens(
+                    amount_a,
+                    amount_b,
+                    pool.total_liquidity_a,
+                    pool.total_liquidity_b,
+                    pool.lp_token_supply,
+                    pool.current_amp(Clock::get()?.unix_timestamp),
+                    pool.fee_rate,
+                )?
+            }
         };
         
         require!(lp_tokens >= min_lp_tokens, AMMError::InsufficientLPTokens);
         
-        // Update pool state
-        pool.total_liquidity_a = pool.total_liquidity_a.checked_add(amount_a).unwrap();
-        pool.total_liquidity_b = pool.total_liquidity_b.checked_add(amount_b).unwrap();
-        pool.lp_token_supply = pool.lp_token_supply.checked_add(lp_tokens).unwrap();
+        // Accumulate prices over the elapsed time before the reserves move
+        pool.accumulate_prices(Clock::get()?.unix_timestamp)?;
+        
+        // Update pool state (locked minimum liquidity counts towards supply but has no owner)
+        pool.total_liquidity_a = pool.total_liquidity_a.checked_add(amount_a)
+            .ok_or(AMMError::MathOverflow)?;
+        pool.total_liquidity_b = pool.total_liquidity_b.checked_add(amount_b)
+            .ok_or(AMMError::MathOverflow)?;
+        if is_initial_deposit {
+            pool.lp_token_supply = MINIMUM_LIQUIDITY;
+        }
+        pool.lp_token_supply = pool.lp_token_supply.checked_add(lp_tokens)
+            .ok_or(AMMError::MathOverflow)?;
+        pool.refresh_current_price();
+        if let Some(observations) = ctx.accounts.observations.as_mut() {
+            observations.record(pool);
+        }
+        
+        // Mint LP tokens to the provider, signed by the pool
+        let token_a_key = ctx.accounts.token_a_mint.key();
+        let token_b_key = ctx.accounts.token_b_mint.key();
+        let fee_rate_bytes = pool.fee_rate.to_le_bytes();
+        let pool_seeds = &[
+            b"pool",
+            token_a_key.as_ref(),
+            token_b_key.as_ref(),
+            fee_rate_bytes.as_ref(),
+            &[pool.bump],
+        ];
+        let pool_signer = &[&pool_seeds[..]];
+        
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_2022_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.user_lp_token.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                pool_signer,
+            ),
+            lp_tokens,
+        )?;
         
         msg!("✅ REAL liquidity added: {} LP tokens minted", lp_tokens);
         Ok(lp_tokens)
     }
 
+    /// Withdraw liquidity by burning the provider's LP share
+    pub fn remove_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveLiquidity<'info>>,
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+    ) -> Result<()> {
+        msg!("💧 Removing liquidity: {} LP tokens", lp_amount);
+        
+        ctx.accounts.withdraw(ctx.remaining_accounts, lp_amount, min_amount_a, min_amount_b, false, false)?;
+        Ok(())
+    }
+
+    /// Exit a pool in emergency mode, optionally leaving out a side whose hook
+    /// program is failing. A skipped side's share leaves the reserves and is recorded
+    /// in the caller's `emergency_claim` (see `open_emergency_claim`), to be paid by
+    /// `claim_emergency_withdrawal`; its user account is still passed but its hook
+    /// accounts are not.
+    pub fn emergency_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveLiquidity<'info>>,
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        skip_a: bool,
+        skip_b: bool,
+    ) -> Result<()> {
+        require!(ctx.accounts.pool.emergency_mode, AMMError::NotInEmergencyMode);
+        require!(!(skip_a && skip_b), AMMError::InvalidAmount);
+        
+        msg!("🚨 Emergency withdraw: {} LP tokens (skip A: {}, skip B: {})", lp_amount, skip_a, skip_b);
+        
+        let (amount_a, amount_b) = ctx.accounts.withdraw(
+            ctx.remaining_accounts,
+            lp_amount,
+            min_amount_a,
+            min_amount_b,
+            skip_a,
+            skip_b,
+        )?;
+        
+        emit!(EmergencyWithdrawEvent {
+            pool: ctx.accounts.pool.key(),
+            user: ctx.accounts.user.key(),
+            lp_amount,
+            amount_a,
+            amount_b,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Create the account recording what emergency exits from a pool or weighted
+    /// pool leave owed to the caller
+    pub fn open_emergency_claim(ctx: Context<OpenEmergencyClaim>) -> Result<()> {
+        let claim = &mut ctx.accounts.emergency_claim;
+        claim.pool = ctx.accounts.pool.key();
+        claim.owner = ctx.accounts.user.key();
+        claim.amounts = Vec::new();
+        claim.bump = ctx.bumps.emergency_claim;
+        
+        msg!("🧾 Emergency claim opened for pool: {}", claim.pool);
+        Ok(())
+    }
+
+    /// Pay out what `emergency_withdraw` left owed, once the skipped side's hook
+    /// program works again. A side still failing can be skipped again; sides with
+    /// nothing owed are skipped and need no hook accounts.
+    pub fn claim_emergency_withdrawal<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveLiquidity<'info>>,
+        skip_a: bool,
+        skip_b: bool,
+    ) -> Result<()> {
+        let claim = ctx.accounts.emergency_claim.as_mut()
+            .ok_or(AMMError::EmergencyClaimRequired)?;
+        let amounts = claim.take(&[skip_a, skip_b]);
+        require!(amounts.iter().any(|amount| *amount > 0), AMMError::NothingToClaim);
+        
+        ctx.accounts.pay_out(ctx.remaining_accounts, amounts[0], amounts[1], amounts[0] == 0, amounts[1] == 0)?;
+        
+        msg!("🧾 Emergency claim paid: {} A, {} B", amounts[0], amounts[1]);
+        
+        emit!(EmergencyClaimPaidEvent {
+            pool: ctx.accounts.pool.key(),
+            user: ctx.accounts.user.key(),
+            amounts,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
     /// REAL swap tokens through the pool
-    pub fn swap(
-        ctx: Context<Swap>,
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
         amount_in: u64,
         minimum_amount_out: u64,
-        a_to_b: bool, // true = A to B, false = B to A
     ) -> Result<u64> {
-        let pool = &ctx.accounts.pool;
+        // Direction follows the input token account's mint
+        let a_to_b = ctx.accounts.prepare(amount_in)?;
+        let (mint_in_info, mint_out_info) = ctx.accounts.mints(a_to_b);
+        let quote = quote_swap_amounts(
+            &ctx.accounts.pool,
+            ctx.accounts.amm_config.protocol_fee_rate,
+            &mint_in_info,
+            &mint_out_info,
+            a_to_b,
+            SwapMode::ExactIn,
+            amount_in,
+        )?;
         
-        msg!("🔄 REAL Swap: {} input, direction: {}", 
-             amount_in, if a_to_b { "A→B" } else { "B→A" });
+        require!(quote.amount_out >= minimum_amount_out, AMMError::InsufficientOutput);
         
-        // Validate transfer hooks if enabled
-        if pool.hook_enabled {
-            if a_to_b {
-                validate_transfer_hooks_real(&ctx.accounts.token_a_mint)?;
-                validate_transfer_hooks_real(&ctx.accounts.token_b_mint)?;
-            } else {
-                validate_transfer_hooks_real(&ctx.accounts.token_b_mint)?;
-                validate_transfer_hooks_real(&ctx.accounts.token_a_mint)?;
-            }
-            msg!("✅ Transfer hooks validated");
+        ctx.accounts.settle(ctx.remaining_accounts, a_to_b, &quote)?;
+        
+        let pool = &ctx.accounts.pool;
+        msg!("💰 REAL Swap completed: {} output (fee: {}, protocol: {})",
+             quote.amount_out, quote.fee_amount, quote.protocol_fee);
+        if quote.transfer_fee_in > 0 || quote.transfer_fee_out > 0 {
+            msg!("🏷️ Transfer fees withheld: in={}, out={}", quote.transfer_fee_in, quote.transfer_fee_out);
         }
+        msg!("📊 New reserves: A={}, B={}", pool.total_liquidity_a, pool.total_liquidity_b);
         
-        // Calculate swap output using constant product formula
-        let (reserve_in, reserve_out) = if a_to_b {
-            (pool.total_liquidity_a, pool.total_liquidity_b)
-        } else {
-            (pool.total_liquidity_b, pool.total_liquidity_a)
-        };
+        Ok(quote.amount_out)
+    }
+
+    /// Swap for an exact output amount, paying at most `maximum_amount_in`.
+    ///
+    /// `amount_out` is what arrives in the user's account, after any transfer fee of the
+    /// output mint. Direction follows the input token account's mint, as in `swap`.
+    pub fn swap_exact_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+    ) -> Result<u64> {
+        let a_to_b = ctx.accounts.prepare(maximum_amount_in)?;
+        let (mint_in_info, mint_out_info) = ctx.accounts.mints(a_to_b);
+        let quote = quote_swap_amounts(
+            &ctx.accounts.pool,
+            ctx.accounts.amm_config.protocol_fee_rate,
+            &mint_in_info,
+            &mint_out_info,
+            a_to_b,
+            SwapMode::ExactOut,
+            amount_out,
+        )?;
         
-        // Apply fee
-        let fee_amount = amount_in.checked_mul(pool.fee_rate).unwrap() / 10000;
-        let amount_in_after_fee = amount_in.checked_sub(fee_amount).unwrap();
+        require!(quote.amount_in <= maximum_amount_in, AMMError::SlippageExceeded);
         
-        // Constant product: x * y = k
-        let denominator = reserve_in.checked_add(amount_in_after_fee).unwrap();
-        let new_reserve_out = reserve_in.checked_mul(reserve_out).unwrap()
-            .checked_div(denominator).unwrap();
-        let amount_out = reserve_out.checked_sub(new_reserve_out).unwrap();
+        ctx.accounts.settle(ctx.remaining_accounts, a_to_b, &quote)?;
         
-        require!(amount_out >= minimum_amount_out, AMMError::InsufficientOutput);
+        let pool = &ctx.accounts.pool;
+        msg!("💰 Exact-out swap completed: {} input for {} output (fee: {}, protocol: {})",
+             quote.amount_in, quote.amount_out, quote.fee_amount, quote.protocol_fee);
+        msg!("📊 New reserves: A={}, B={}", pool.total_liquidity_a, pool.total_liquidity_b);
+        
+        Ok(quote.amount_in)
+    }
 
-        // Get decimals from mint accounts
-        let token_a_decimals = get_mint_decimals(&ctx.accounts.token_a_mint)?;
-        let token_b_decimals = get_mint_decimals(&ctx.accounts.token_b_mint)?;
+    /// Quote a swap without moving tokens; the result is returned via return data
+    pub fn quote_swap(
+        ctx: Context<QuoteSwap>,
+        amount: u64, // input for ExactIn, output for ExactOut
+        input_mint: Pubkey,
+        mode: SwapMode,
+    ) -> Result<SwapQuoteResult> {
+        let pool = &ctx.accounts.pool;
+        let a_to_b = if input_mint == pool.token_a_mint {
+            true
+        } else if input_mint == pool.token_b_mint {
+            false
+        } else {
+            return Err(AMMError::InvalidTokenPair.into());
+        };
+        let (mint_in_info, mint_out_info) = if a_to_b {
+            (ctx.accounts.token_a_mint.to_account_info(), ctx.accounts.token_b_mint.to_account_info())
+        } else {
+            (ctx.accounts.token_b_mint.to_account_info(), ctx.accounts.token_a_mint.to_account_info())
+        };
+        
+        let quote = quote_swap_amounts(
+            pool,
+            ctx.accounts.amm_config.protocol_fee_rate,
+            &mint_in_info,
+            &mint_out_info,
+            a_to_b,
+            mode,
+            amount,
+        )?;
+        
+        msg!("🧮 Quote: {} in → {} out (fee: {}, impact: {}bp)",
+             quote.amount_in, quote.amount_out, quote.fee_amount, quote.price_impact_bps);
+        Ok(quote)
+    }
 
-        // REAL Token-2022 transfers
-        if a_to_b {
-            // Transfer Token A from user to pool
-            let transfer_in_ix = transfer_checked(
-                &spl_token_2022::id(),
-                &ctx.accounts.user_token_in.key(),
-                &ctx.accounts.token_a_mint.key(),
-                &ctx.accounts.pool_token_a.key(),
-                &ctx.accounts.user.key(),
-                &[],
-                amount_in,
-                token_a_decimals,
+    /// Swap through a sequence of pools atomically.
+    ///
+    /// `path` lists the mints visited, input first. For each hop, remaining_accounts holds
+    /// `[pool, mint_in, mint_out, vault_in, vault_out, user_token_out]`, optionally the
+    /// pool's writable observations account, then the hook groups of mint_in and mint_out.
+    /// Each hop pays into the user's account for the next mint, which is the source of
+    /// the following hop. Hops without an observations account record no observation.
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        path: Vec<Pubkey>,
+    ) -> Result<u64> {
+        ctx.accounts.amm_config.require_not_paused()?;
+        require!(
+            path.len() >= 2 && path.len() <= MAX_ROUTE_HOPS + 1,
+            AMMError::InvalidRoute
+        );
+        require_keys_eq!(ctx.accounts.user_token_in.mint, path[0], AMMError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.user_token_in.owner, ctx.accounts.user.key(), AMMError::InvalidTokenAccount);
+        
+        msg!("🧭 Route swap: {} input over {} hops", amount_in, path.len() - 1);
+        
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        let protocol_fee_rate = ctx.accounts.amm_config.protocol_fee_rate;
+        let now = Clock::get()?.unix_timestamp;
+        
+        let mut remaining = ctx.remaining_accounts;
+        let mut source = ctx.accounts.user_token_in.to_account_info();
+        let mut amount = amount_in;
+        
+        for (hop, mints) in path.windows(2).enumerate() {
+            require!(remaining.len() >= ROUTE_HOP_ACCOUNTS, AMMError::InvalidRoute);
+            let (hop_accounts, rest) = remaining.split_at(ROUTE_HOP_ACCOUNTS);
+            remaining = rest;
+            let [pool_info, mint_in_info, mint_out_info, vault_in, vault_out, user_token_out] = hop_accounts else {
+                return Err(AMMError::InvalidRoute.into());
+            };
+            
+            // Only pools created by this program deserialize here
+            let mut pool = Account::<Pool>::try_from(pool_info)?;
+            pool.require_not_paused()?;
+            
+            // An observations account, if present, is told apart from hook accounts and
+            // the next hop's pool by its owner and discriminator
+            let mut observations = match remaining.first() {
+                Some(info) if info.owner == &crate::ID
+                    && info.try_borrow_data()?.starts_with(&Observations::DISCRIMINATOR) =>
+                {
+                    remaining = &remaining[1..];
+                    let observations = Account::<Observations>::try_from(info)?;
+                    require_keys_eq!(observations.pool, pool.key(), AMMError::InvalidRoute);
+                    Some(observations)
+                }
+                _ => None,
+            };
+            require_keys_eq!(mint_in_info.key(), mints[0], AMMError::InvalidRoute);
+            require_keys_eq!(mint_out_info.key(), mints[1], AMMError::InvalidRoute);
+            let a_to_b = if mints[0] == pool.token_a_mint && mints[1] == pool.token_b_mint {
+                true
+            } else if mints[0] == pool.token_b_mint && mints[1] == pool.token_a_mint {
+                false
+            } else {
+                return Err(AMMError::InvalidTokenPair.into());
+            };
+            let (expected_vault_in, expected_vault_out) = if a_to_b {
+                (pool.token_a_vault, pool.token_b_vault)
+            } else {
+                (pool.token_b_vault, pool.token_a_vault)
+            };
+            require_keys_eq!(vault_in.key(), expected_vault_in, AMMError::InvalidVault);
+            require_keys_eq!(vault_out.key(), expected_vault_out, AMMError::InvalidVault);
+            
+            let destination = InterfaceAccount::<TokenAccount>::try_from(user_token_out)?;
+            require_keys_eq!(destination.mint, mints[1], AMMError::InvalidTokenAccount);
+            require_keys_eq!(destination.owner, ctx.accounts.user.key(), AMMError::InvalidTokenAccount);
+            
+            // Validate the mints' current hooks (a mint authority can change its hook program)
+            let (mint_a_info, mint_b_info) = if a_to_b {
+                (mint_in_info, mint_out_info)
+            } else {
+                (mint_out_info, mint_in_info)
+            };
+            let hook_a = validate_transfer_hooks_real(mint_a_info, &ctx.accounts.amm_config, hook_registry)?;
+            let hook_b = validate_transfer_hooks_real(mint_b_info, &ctx.accounts.amm_config, hook_registry)?;
+            pool.refresh_hook_programs(hook_a, hook_b);
+            
+            // Same curve and fee handling as `swap`
+            let quote = quote_swap_amounts(
+                &pool,
+                protocol_fee_rate,
+                mint_in_info,
+                mint_out_info,
+                a_to_b,
+                SwapMode::ExactIn,
+                amount,
             )?;
-
-            invoke(
-                &transfer_in_ix,
-                &[
-                    ctx.accounts.user_token_in.to_account_info(),
-                    ctx.accounts.token_a_mint.to_account_info(),
-                    ctx.accounts.pool_token_a.to_account_info(),
-                    ctx.accounts.user.to_account_info(),
-                    ctx.accounts.token_2022_program.to_account_info(),
-                ],
+            
+            let hook_accounts_in = take_hook_accounts(mint_in_info, &mut remaining)?;
+            let hook_accounts_out = take_hook_accounts(mint_out_info, &mut remaining)?;
+            
+            transfer_checked_with_hooks(
+                source.clone(),
+                mint_in_info.clone(),
+                vault_in.clone(),
+                ctx.accounts.user.to_account_info(),
+                hook_accounts_in,
+                amount,
+                get_mint_decimals(mint_in_info)?,
+                &[],
             )?;
-
-            // Transfer Token B from pool to user
-            let token_a_key = ctx.accounts.token_a_mint.key();
-            let token_b_key = ctx.accounts.token_b_mint.key();
+            
+            let fee_rate_bytes = pool.fee_rate.to_le_bytes();
             let pool_seeds = &[
                 b"pool",
-                token_a_key.as_ref(),
-                token_b_key.as_ref(),
+                pool.token_a_mint.as_ref(),
+                pool.token_b_mint.as_ref(),
+                fee_rate_bytes.as_ref(),
                 &[pool.bump],
             ];
             let pool_signer = &[&pool_seeds[..]];
-
-            let transfer_out_ix = transfer_checked(
-                &spl_token_2022::id(),
-                &ctx.accounts.pool_token_b.key(),
-                &ctx.accounts.token_b_mint.key(),
-                &ctx.accounts.user_token_out.key(),
-                &ctx.accounts.pool.key(),
-                &[],
-                amount_out,
-                token_b_decimals,
-            )?;
-
-            invoke_signed(
-                &transfer_out_ix,
-                &[
-                    ctx.accounts.pool_token_b.to_account_info(),
-                    ctx.accounts.token_b_mint.to_account_info(),
-                    ctx.accounts.user_token_out.to_account_info(),
-                    ctx.accounts.pool.to_account_info(),
-                    ctx.accounts.token_2022_program.to_account_info(),
-                ],
+            
+            transfer_checked_with_hooks(
+                vault_out.clone(),
+                mint_out_info.clone(),
+                user_token_out.clone(),
+                pool_info.clone(),
+                hook_accounts_out,
+                quote.pool_amount_out,
+                get_mint_decimals(mint_out_info)?,
                 pool_signer,
             )?;
-        } else {
-            // B to A swap - similar implementation
-            let transfer_in_ix = transfer_checked(
-                &spl_token_2022::id(),
-                &ctx.accounts.user_token_in.key(),
-                &ctx.accounts.token_b_mint.key(),
-                &ctx.accounts.pool_token_b.key(),
-                &ctx.accounts.user.key(),
-                &[],
-                amount_in,
-                token_b_decimals,
-            )?;
-
-            invoke(
-                &transfer_in_ix,
-                &[
-                    ctx.accounts.user_token_in.to_account_info(),
-                    ctx.accounts.token_b_mint.to_account_info(),
-                    ctx.accounts.pool_token_b.to_account_info(),
-                    ctx.accounts.user.to_account_info(),
-                    ctx.accounts.token_2022_program.to_account_info(),
-                ],
-            )?;
-
-            let token_a_key = ctx.accounts.token_a_mint.key();
-            let token_b_key = ctx.accounts.token_b_mint.key();
-            let pool_seeds = &[
-                b"pool",
-                token_a_key.as_ref(),
-                token_b_key.as_ref(),
-                &[pool.bump],
-            ];
-            let pool_signer = &[&pool_seeds[..]];
+            
+            pool.accumulate_prices(now)?;
+            pool.apply_swap(a_to_b, quote.amount_received, quote.pool_amount_out, quote.protocol_fee)?;
+            pool.exit(&crate::ID)?;
+            if let Some(observations) = observations.as_mut() {
+                observations.record(&pool);
+                observations.exit(&crate::ID)?;
+            }
+            
+            msg!("🔀 Hop {}: {} {} → {} {}", hop + 1, amount, mints[0], quote.amount_out, mints[1]);
+            
+            source = user_token_out.clone();
+            amount = quote.amount_out;
+        }
+        
+        // Slippage is only checked on the final output
+        require!(amount >= minimum_amount_out, AMMError::InsufficientOutput);
+        
+        msg!("💰 Route swap completed: {} output", amount);
+        Ok(amount)
+    }
+
+    /// Start ramping a StableSwap pool's amplification towards `target_amp`
+    pub fn ramp_amp(
+        ctx: Context<UpdatePoolAmp>,
+        target_amp: u64,
+        ramp_end: i64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.pool_type == PoolType::StableSwap, AMMError::InvalidPoolType);
+        require!(
+            (stable_math::MIN_AMP..=stable_math::MAX_AMP).contains(&target_amp),
+            AMMError::InvalidAmplification
+        );
+        
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ramp_end >= now + stable_math::MIN_RAMP_DURATION,
+            AMMError::InvalidAmplification
+        );
+        
+        // Bound each ramp to a MAX_AMP_CHANGE factor either way
+        let current_amp = pool.current_amp(now);
+        require!(
+            target_amp <= current_amp * stable_math::MAX_AMP_CHANGE
+                && target_amp * stable_math::MAX_AMP_CHANGE >= current_amp,
+            AMMError::InvalidAmplification
+        );
+        
+        pool.amp_initial = current_amp;
+        pool.amp_target = target_amp;
+        pool.amp_ramp_start = now;
+        pool.amp_ramp_end = ramp_end;
+        
+        msg!("📈 Amp ramp: {} → {} until {}", current_amp, target_amp, ramp_end);
+        
+        emit!(AmpRampStartedEvent {
+            pool: pool.key(),
+            initial_amp: current_amp,
+            target_amp,
+            ramp_start: now,
+            ramp_end,
+        });
+        
+        Ok(())
+    }
+
+    /// Stop an amplification ramp at its current value
+    pub fn stop_ramp_amp(ctx: Context<UpdatePoolAmp>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.pool_type == PoolType::StableSwap, AMMError::InvalidPoolType);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let current_amp = pool.current_amp(now);
+        pool.amp_initial = current_amp;
+        pool.amp_target = current_amp;
+        pool.amp_ramp_start = now;
+        pool.amp_ramp_end = now;
+        
+        msg!("⏹️ Amp ramp stopped at {}", current_amp);
+        
+        emit!(AmpRampStoppedEvent {
+            pool: pool.key(),
+            amp: current_amp,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+
+    /// Send a pool's accrued protocol fees to the treasury (fee manager)
+    pub fn collect_protocol_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectProtocolFees<'info>>,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let amount_a = pool.protocol_fees_a;
+        let amount_b = pool.protocol_fees_b;
+        
+        require!(amount_a > 0 || amount_b > 0, AMMError::NoProtocolFees);
+        
+        let token_a_decimals = get_mint_decimals(&ctx.accounts.token_a_mint)?;
+        let token_b_decimals = get_mint_decimals(&ctx.accounts.token_b_mint)?;
+        
+        let token_a_key = ctx.accounts.token_a_mint.key();
+        let token_b_key = ctx.accounts.token_b_mint.key();
+        let fee_rate_bytes = pool.fee_rate.to_le_bytes();
+        let pool_seeds = &[
+            b"pool",
+            token_a_key.as_ref(),
+            token_b_key.as_ref(),
+            fee_rate_bytes.as_ref(),
+            &[pool.bump],
+        ];
+        let pool_signer = &[&pool_seeds[..]];
+        
+        // Hook accounts in remaining_accounts: token A group, then token B group
+        let mut remaining = ctx.remaining_accounts;
+        let hook_accounts_a = take_hook_accounts(&ctx.accounts.token_a_mint, &mut remaining)?;
+        let hook_accounts_b = take_hook_accounts(&ctx.accounts.token_b_mint, &mut remaining)?;
+        
+        transfer_checked_with_hooks(
+            ctx.accounts.pool_token_a.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.treasury_token_a.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            hook_accounts_a,
+            amount_a,
+            token_a_decimals,
+            pool_signer,
+        )?;
+        
+        transfer_checked_with_hooks(
+            ctx.accounts.pool_token_b.to_account_info(),
+            ctx.accounts.token_b_mint.to_account_info(),
+            ctx.accounts.treasury_token_b.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            hook_accounts_b,
+            amount_b,
+            token_b_decimals,
+            pool_signer,
+        )?;
+        
+        let pool = &mut ctx.accounts.pool;
+        pool.protocol_fees_a = 0;
+        pool.protocol_fees_b = 0;
+        
+        msg!("🏦 Protocol fees collected: {} A, {} B", amount_a, amount_b);
+        
+        emit!(ProtocolFeesCollectedEvent {
+            pool: pool.key(),
+            treasury: ctx.accounts.amm_config.treasury,
+            amount_a,
+            amount_b,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Create the observation ring buffer that backs `observe` for a pool
+    pub fn initialize_observations(ctx: Context<InitializeObservations>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.accumulate_prices(Clock::get()?.unix_timestamp)?;
+        
+        let observations = &mut ctx.accounts.observations;
+        observations.pool = pool.key();
+        observations.index = 0;
+        observations.observations = Vec::new();
+        observations.bump = ctx.bumps.observations;
+        observations.record(pool);
+        
+        msg!("🔭 Observations initialized for pool: {}", pool.key());
+        Ok(())
+    }
+
+    /// Time-weighted average prices over at least the last `window_seconds`, via return data
+    pub fn observe(ctx: Context<Observe>, window_seconds: u32) -> Result<TwapResult> {
+        require!(window_seconds > 0, AMMError::InvalidObservationWindow);
+        
+        let pool = &ctx.accounts.pool;
+        let now = Clock::get()?.unix_timestamp;
+        let (price_a_cumulative, price_b_cumulative) = pool.cumulative_prices_at(now)?;
+        
+        // Newest observation at least `window_seconds` old
+        let target = now - window_seconds as i64;
+        let start = ctx.accounts.observations.observations
+            .iter()
+            .filter(|o| o.timestamp <= target)
+            .max_by_key(|o| o.timestamp)
+            .ok_or(AMMError::InsufficientObservationHistory)?;
+        
+        let elapsed = now - start.timestamp;
+        let result = TwapResult {
+            price_a: math::twap(start.price_a_cumulative, price_a_cumulative, elapsed)?,
+            price_b: math::twap(start.price_b_cumulative, price_b_cumulative, elapsed)?,
+            window_seconds: elapsed,
+        };
+        
+        msg!("🔭 TWAP over {}s: A={}, B={}", elapsed, result.price_a, result.price_b);
+        Ok(result)
+    }
+
+    /// Create a concentrated-liquidity pool on a fee tier (mints sorted, A < B)
+    pub fn create_cl_pool(
+        ctx: Context<CreateClPool>,
+        fee_rate: u64,      // one of AMMConfig.fee_tiers, part of the pool seeds
+        tick_spacing: u16,
+        initial_sqrt_price_x64: u128, // sqrt(B per A) in Q64.64
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.amm_config.fee_tiers.contains(&fee_rate),
+            AMMError::InvalidFeeTier
+        );
+        require!(fee_rate < math::FEE_RATE_DENOMINATOR, AMMError::InvalidFeeRate);
+        require!(
+            tick_spacing > 0 && tick_spacing <= MAX_TICK_SPACING,
+            AMMError::InvalidTickSpacing
+        );
+        let current_tick = clmm_math::tick_from_sqrt_price(initial_sqrt_price_x64)?;
+        
+        // Verify mints are Token-2022
+        require!(
+            ctx.accounts.token_a_mint.owner == &spl_token_2022::id(),
+            AMMError::InvalidTokenProgram
+        );
+        require!(
+            ctx.accounts.token_b_mint.owner == &spl_token_2022::id(),
+            AMMError::InvalidTokenProgram
+        );
+        
+        // Hooked mints must use a registry-approved hook program
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        validate_transfer_hooks_real(&ctx.accounts.token_a_mint, &ctx.accounts.amm_config, hook_registry)?;
+        validate_transfer_hooks_real(&ctx.accounts.token_b_mint, &ctx.accounts.amm_config, hook_registry)?;
+        
+        let token_a_info = read_mint_transfer_info(&ctx.accounts.token_a_mint)?;
+        let token_b_info = read_mint_transfer_info(&ctx.accounts.token_b_mint)?;
+        require!(
+            token_a_info.flags & MINT_FLAG_NON_TRANSFERABLE == 0
+                && token_b_info.flags & MINT_FLAG_NON_TRANSFERABLE == 0,
+            AMMError::UnsupportedMintExtension
+        );
+        require!(
+            !ctx.accounts.token_a_vault.is_frozen() && !ctx.accounts.token_b_vault.is_frozen(),
+            AMMError::UnsupportedMintExtension
+        );
+        
+        let cl_pool = &mut ctx.accounts.cl_pool;
+        cl_pool.token_a_mint = ctx.accounts.token_a_mint.key();
+        cl_pool.token_b_mint = ctx.accounts.token_b_mint.key();
+        cl_pool.creator = ctx.accounts.creator.key();
+        cl_pool.created_at = Clock::get()?.unix_timestamp;
+        cl_pool.fee_rate = fee_rate;
+        cl_pool.tick_spacing = tick_spacing;
+        cl_pool.bump = ctx.bumps.cl_pool;
+        cl_pool.token_a_vault = ctx.accounts.token_a_vault.key();
+        cl_pool.token_b_vault = ctx.accounts.token_b_vault.key();
+        cl_pool.token_a_hook_program = token_a_info.hook_program_id;
+        cl_pool.token_b_hook_program = token_b_info.hook_program_id;
+        cl_pool.token_a_flags = token_a_info.flags;
+        cl_pool.token_b_flags = token_b_info.flags;
+        cl_pool.sqrt_price_x64 = initial_sqrt_price_x64;
+        cl_pool.current_tick = current_tick;
+        cl_pool.liquidity = 0;
+        cl_pool.fee_growth_global_a_x64 = 0;
+        cl_pool.fee_growth_global_b_x64 = 0;
+        cl_pool.paused = false;
+        cl_pool.emergency_mode = false;
+        
+        let amm_config = &mut ctx.accounts.amm_config;
+        amm_config.total_pools += 1;
+        
+        msg!("🎯 Concentrated pool created for {}/{}", cl_pool.token_a_mint, cl_pool.token_b_mint);
+        msg!("🏦 Vaults: A={}, B={}", cl_pool.token_a_vault, cl_pool.token_b_vault);
+        msg!("💰 Initial sqrt price: {} (tick {})", initial_sqrt_price_x64, current_tick);
+        msg!("💸 Fee tier: {}bp, tick spacing: {}", fee_rate, tick_spacing);
+        msg!("🔗 Hooks: A={:?}, B={:?}", cl_pool.token_a_hook_program, cl_pool.token_b_hook_program);
+        Ok(())
+    }
+
+    /// Create the tick array starting at `start_tick_index`
+    pub fn initialize_tick_array(
+        ctx: Context<InitializeTickArray>,
+        start_tick_index: i32, // multiple of TICK_ARRAY_SIZE * tick_spacing
+    ) -> Result<()> {
+        let cl_pool = &ctx.accounts.cl_pool;
+        require!(
+            start_tick_index == TickArray::start_index_for(start_tick_index, cl_pool.tick_spacing)
+                && start_tick_index >= TickArray::start_index_for(clmm_math::MIN_TICK, cl_pool.tick_spacing)
+                && start_tick_index <= clmm_math::MAX_TICK,
+            AMMError::InvalidTickArray
+        );
+        
+        let tick_array = &mut ctx.accounts.tick_array;
+        tick_array.pool = cl_pool.key();
+        tick_array.start_tick_index = start_tick_index;
+        tick_array.bump = ctx.bumps.tick_array;
+        
+        msg!("📏 Tick array initialized at {} for pool: {}", start_tick_index, cl_pool.key());
+        Ok(())
+    }
+
+    /// Open an empty position over `[tick_lower, tick_upper)`
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        ctx.accounts.cl_pool.validate_tick_range(tick_lower, tick_upper)?;
+        
+        let position = &mut ctx.accounts.position;
+        position.pool = ctx.accounts.cl_pool.key();
+        position.owner = ctx.accounts.owner.key();
+        position.tick_lower = tick_lower;
+        position.tick_upper = tick_upper;
+        position.liquidity = 0;
+        position.fee_growth_inside_a_last_x64 = 0;
+        position.fee_growth_inside_b_last_x64 = 0;
+        position.fees_owed_a = 0;
+        position.fees_owed_b = 0;
+        position.bump = ctx.bumps.position;
+        
+        msg!("📍 Position opened: [{}, {}) in pool {}", tick_lower, tick_upper, position.pool);
+        Ok(())
+    }
+
+    /// Add liquidity to a position, paying at most the given amounts (before transfer fees)
+    pub fn increase_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyPosition<'info>>,
+        liquidity_delta: u128,
+        amount_a_max: u64,
+        amount_b_max: u64,
+    ) -> Result<()> {
+        require!(liquidity_delta > 0, AMMError::InvalidAmount);
+        ctx.accounts.amm_config.require_not_paused()?;
+        ctx.accounts.cl_pool.require_not_paused()?;
+        let delta = i128::try_from(liquidity_delta).map_err(|_| AMMError::MathOverflow)?;
+        
+        // Validate the mints' current hooks (a mint authority can change its hook program)
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        let hook_a = validate_transfer_hooks_real(&ctx.accounts.token_a_mint, &ctx.accounts.amm_config, hook_registry)?;
+        let hook_b = validate_transfer_hooks_real(&ctx.accounts.token_b_mint, &ctx.accounts.amm_config, hook_registry)?;
+        ctx.accounts.cl_pool.refresh_hook_programs(hook_a, hook_b);
+        
+        // Ticks first so a newly initialized tick starts with the right outside growth
+        ctx.accounts.update_ticks(delta)?;
+        ctx.accounts.update_position_fees()?;
+        ctx.accounts.apply_liquidity_delta(delta)?;
+        
+        // Amounts the vaults must receive, rounded up in favour of the pool
+        let cl_pool = &ctx.accounts.cl_pool;
+        let position = &ctx.accounts.position;
+        let (amount_a, amount_b) = clmm_math::amounts_for_liquidity(
+            cl_pool.sqrt_price_x64,
+            position.tick_lower,
+            position.tick_upper,
+            liquidity_delta,
+            true,
+        )?;
+        
+        // Gross up for transfer fees so the full amounts reach the vaults
+        let gross_amount_a = if cl_pool.token_a_has_transfer_fee() {
+            get_pre_transfer_fee_amount(&ctx.accounts.token_a_mint, amount_a)?
+        } else {
+            amount_a
+        };
+        let gross_amount_b = if cl_pool.token_b_has_transfer_fee() {
+            get_pre_transfer_fee_amount(&ctx.accounts.token_b_mint, amount_b)?
+        } else {
+            amount_b
+        };
+        require!(gross_amount_a <= amount_a_max, AMMError::SlippageExceeded);
+        require!(gross_amount_b <= amount_b_max, AMMError::SlippageExceeded);
+        
+        let token_a_decimals = get_mint_decimals(&ctx.accounts.token_a_mint)?;
+        let token_b_decimals = get_mint_decimals(&ctx.accounts.token_b_mint)?;
+        
+        // Hook accounts in remaining_accounts: token A group, then token B group
+        let mut remaining = ctx.remaining_accounts;
+        let hook_accounts_a = take_hook_accounts(&ctx.accounts.token_a_mint, &mut remaining)?;
+        let hook_accounts_b = take_hook_accounts(&ctx.accounts.token_b_mint, &mut remaining)?;
+        
+        transfer_checked_with_hooks(
+            ctx.accounts.user_token_a.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.pool_token_a.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            hook_accounts_a,
+            gross_amount_a,
+            token_a_decimals,
+            &[],
+        )?;
+        
+        transfer_checked_with_hooks(
+            ctx.accounts.user_token_b.to_account_info(),
+            ctx.accounts.token_b_mint.to_account_info(),
+            ctx.accounts.pool_token_b.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            hook_accounts_b,
+            gross_amount_b,
+            token_b_decimals,
+            &[],
+        )?;
+        
+        msg!("💧 Position liquidity +{}: paid {} A, {} B", liquidity_delta, gross_amount_a, gross_amount_b);
+        msg!("📊 Position liquidity: {}, pool active liquidity: {}",
+             ctx.accounts.position.liquidity, ctx.accounts.cl_pool.liquidity);
+        Ok(())
+    }
+
+    /// Remove liquidity from a position; slippage limits apply after transfer fees
+    pub fn decrease_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyPosition<'info>>,
+        liquidity_delta: u128,
+        amount_a_min: u64,
+        amount_b_min: u64,
+    ) -> Result<()> {
+        require!(liquidity_delta > 0, AMMError::InvalidAmount);
+        require!(
+            liquidity_delta <= ctx.accounts.position.liquidity,
+            AMMError::InsufficientLiquidity
+        );
+        let delta = i128::try_from(liquidity_delta).map_err(|_| AMMError::MathOverflow)?;
+        
+        // No registry check here: LPs can always withdraw, even if a hook was revoked
+        
+        // Settle fees while the ticks still hold their outside growth, then release them
+        ctx.accounts.update_position_fees()?;
+        ctx.accounts.update_ticks(-delta)?;
+        ctx.accounts.apply_liquidity_delta(-delta)?;
+        
+        // Amounts paid out, rounded down in favour of the pool
+        let cl_pool = &ctx.accounts.cl_pool;
+        let position = &ctx.accounts.position;
+        let (amount_a, amount_b) = clmm_math::amounts_for_liquidity(
+            cl_pool.sqrt_price_x64,
+            position.tick_lower,
+            position.tick_upper,
+            liquidity_delta,
+            false,
+        )?;
+        
+        let net_amount_a = if cl_pool.token_a_has_transfer_fee() {
+            amount_a.checked_sub(get_transfer_fee(&ctx.accounts.token_a_mint, amount_a)?)
+                .ok_or(AMMError::MathOverflow)?
+        } else {
+            amount_a
+        };
+        let net_amount_b = if cl_pool.token_b_has_transfer_fee() {
+            amount_b.checked_sub(get_transfer_fee(&ctx.accounts.token_b_mint, amount_b)?)
+                .ok_or(AMMError::MathOverflow)?
+        } else {
+            amount_b
+        };
+        require!(net_amount_a >= amount_a_min, AMMError::SlippageExceeded);
+        require!(net_amount_b >= amount_b_min, AMMError::SlippageExceeded);
+        
+        ctx.accounts.pay_out(ctx.remaining_accounts, amount_a, amount_b, false, false)?;
+        
+        msg!("💧 Position liquidity -{}: received {} A, {} B", liquidity_delta, net_amount_a, net_amount_b);
+        msg!("📊 Position liquidity: {}, pool active liquidity: {}",
+             ctx.accounts.position.liquidity, ctx.accounts.cl_pool.liquidity);
+        Ok(())
+    }
+
+    /// Send a position's earned swap fees to the owner
+    pub fn collect_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyPosition<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.update_position_fees()?;
+        
+        let position = &mut ctx.accounts.position;
+        let (fees_a, fees_b) = (position.fees_owed_a, position.fees_owed_b);
+        require!(fees_a > 0 || fees_b > 0, AMMError::NoFeesOwed);
+        position.fees_owed_a = 0;
+        position.fees_owed_b = 0;
+        
+        ctx.accounts.pay_out(ctx.remaining_accounts, fees_a, fees_b, false, false)?;
+        
+        msg!("🏦 Position fees collected: {} A, {} B", fees_a, fees_b);
+        Ok(())
+    }
+
+    /// Withdraw liquidity from a position in an emergency-mode pool, paying out only
+    /// the sides not skipped. A skipped side's tokens are added to what the position
+    /// is owed, to be collected once its hook program works again. `liquidity_delta`
+    /// may be zero to just collect what is owed.
+    pub fn emergency_decrease_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyPosition<'info>>,
+        liquidity_delta: u128,
+        skip_a: bool,
+        skip_b: bool,
+    ) -> Result<()> {
+        require!(ctx.accounts.cl_pool.emergency_mode, AMMError::NotInEmergencyMode);
+        require!(!(skip_a && skip_b), AMMError::InvalidAmount);
+        require!(
+            liquidity_delta <= ctx.accounts.position.liquidity,
+            AMMError::InsufficientLiquidity
+        );
+        let delta = i128::try_from(liquidity_delta).map_err(|_| AMMError::MathOverflow)?;
+        
+        ctx.accounts.update_position_fees()?;
+        let (amount_a, amount_b) = if delta > 0 {
+            ctx.accounts.update_ticks(-delta)?;
+            ctx.accounts.apply_liquidity_delta(-delta)?;
+            let position = &ctx.accounts.position;
+            clmm_math::amounts_for_liquidity(
+                ctx.accounts.cl_pool.sqrt_price_x64,
+                position.tick_lower,
+                position.tick_upper,
+                liquidity_delta,
+                false,
+            )?
+        } else {
+            (0, 0)
+        };
+        
+        // Everything released is owed first; only the paid sides are cleared
+        let position = &mut ctx.accounts.position;
+        position.fees_owed_a = position.fees_owed_a.checked_add(amount_a)
+            .ok_or(AMMError::MathOverflow)?;
+        position.fees_owed_b = position.fees_owed_b.checked_add(amount_b)
+            .ok_or(AMMError::MathOverflow)?;
+        let paid_a = if skip_a { 0 } else { std::mem::take(&mut position.fees_owed_a) };
+        let paid_b = if skip_b { 0 } else { std::mem::take(&mut position.fees_owed_b) };
+        
+        ctx.accounts.pay_out(ctx.remaining_accounts, paid_a, paid_b, skip_a, skip_b)?;
+        
+        msg!("🚨 Emergency position exit -{}: paid {} A, {} B", liquidity_delta, paid_a, paid_b);
+        
+        emit!(EmergencyPositionExitEvent {
+            pool: ctx.accounts.cl_pool.key(),
+            position: ctx.accounts.position.key(),
+            liquidity: liquidity_delta,
+            amount_a: paid_a,
+            amount_b: paid_b,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Close an empty position and reclaim its rent
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        msg!("📍 Position closed: {}", ctx.accounts.position.key());
+        Ok(())
+    }
+
+    /// Swap through a concentrated-liquidity pool, crossing initialized ticks as needed.
+    ///
+    /// Direction follows the input token account's mint. The swap stops early at
+    /// `sqrt_price_limit_x64` (0 for no limit), in which case only the input the curve
+    /// used is taken. Tick arrays go in swap direction starting with the one holding
+    /// the current tick.
+    pub fn swap_cl<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapCl<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        sqrt_price_limit_x64: u128,
+    ) -> Result<u64> {
+        require!(amount_in > 0, AMMError::InvalidAmount);
+        let a_to_b = ctx.accounts.prepare(amount_in)?;
+        let (mint_in_info, mint_out_info) = ctx.accounts.mints(a_to_b);
+        let (fee_in_side, fee_out_side) = ctx.accounts.cl_pool.transfer_fee_sides(a_to_b);
+        
+        // Transfer fees are withheld in the vault; only what arrives is swapped
+        let transfer_fee_in = if fee_in_side { get_transfer_fee(&mint_in_info, amount_in)? } else { 0 };
+        let amount_received = amount_in.checked_sub(transfer_fee_in).ok_or(AMMError::MathOverflow)?;
+        
+        let step = ctx.accounts.execute(a_to_b, amount_received, sqrt_price_limit_x64)?;
+        
+        // Partial fills pay only for the input the curve consumed
+        let amount_paid = if step.amount_in == amount_received {
+            amount_in
+        } else if fee_in_side {
+            get_pre_transfer_fee_amount(&mint_in_info, step.amount_in)?
+        } else {
+            step.amount_in
+        };
+        let transfer_fee_out = if fee_out_side { get_transfer_fee(&mint_out_info, step.amount_out)? } else { 0 };
+        let amount_out = step.amount_out.checked_sub(transfer_fee_out).ok_or(AMMError::MathOverflow)?;
+        require!(amount_out >= minimum_amount_out, AMMError::InsufficientOutput);
+        
+        ctx.accounts.settle(ctx.remaining_accounts, a_to_b, amount_paid, step.amount_out)?;
+        
+        let cl_pool = &ctx.accounts.cl_pool;
+        msg!("💰 Concentrated swap completed: {} input for {} output (fee: {})",
+             amount_paid, amount_out, step.fee_amount);
+        msg!("📊 Sqrt price: {} (tick {}), active liquidity: {}",
+             cl_pool.sqrt_price_x64, cl_pool.current_tick, cl_pool.liquidity);
+        
+        Ok(amount_out)
+    }
+
+    /// Create a weighted pool of 3-8 assets.
+    ///
+    /// `remaining_accounts` holds `[mint, vault]` per asset, mints sorted ascending; each
+    /// vault is the PDA `[b"vault", weighted_pool, mint]` and is created here. `pool_id`
+    /// must equal `WeightedPool::derive_id` of the mints, weights and fee rate.
+    pub fn create_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateWeightedPool<'info>>,
+        pool_id: [u8; 32],
+        weights: Vec<u64>,  // fixed point, summing to weighted_math::ONE
+        fee_rate: u64,      // one of AMMConfig.fee_tiers
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.amm_config.fee_tiers.contains(&fee_rate),
+            AMMError::InvalidFeeTier
+        );
+        require!(
+            (MIN_WEIGHTED_ASSETS..=MAX_WEIGHTED_ASSETS).contains(&weights.len())
+                && ctx.remaining_accounts.len() == 2 * weights.len(),
+            AMMError::InvalidAssetCount
+        );
+        weighted_math::validate_weights(&weights)?;
+        
+        let mints: Vec<Pubkey> = ctx.remaining_accounts.iter().step_by(2).map(|info| info.key()).collect();
+        require!(
+            pool_id == WeightedPool::derive_id(&mints, &weights, fee_rate),
+            AMMError::InvalidTokenPair
+        );
+        // One pool per basket: mints must be distinct and sorted
+        require!(mints.windows(2).all(|pair| pair[0] < pair[1]), AMMError::InvalidTokenPair);
+        
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        let pool_key = ctx.accounts.weighted_pool.key();
+        let mut assets = Vec::with_capacity(weights.len());
+        
+        for (pair, weight) in ctx.remaining_accounts.chunks(2).zip(&weights) {
+            let (mint_info, vault_info) = (&pair[0], &pair[1]);
+            require!(
+                mint_info.owner == &spl_token_2022::id(),
+                AMMError::InvalidTokenProgram
+            );
+            
+            // Hooked mints must use a registry-approved hook program
+            validate_transfer_hooks_real(mint_info, &ctx.accounts.amm_config, hook_registry)?;
+            let transfer_info = read_mint_transfer_info(mint_info)?;
+            require!(
+                transfer_info.flags & MINT_FLAG_NON_TRANSFERABLE == 0,
+                AMMError::UnsupportedMintExtension
+            );
+            
+            let (vault_key, vault_bump) = Pubkey::find_program_address(
+                &[b"vault", pool_key.as_ref(), mint_info.key.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(vault_info.key(), vault_key, AMMError::InvalidVault);
+            create_pool_vault(
+                ctx.accounts.creator.to_account_info(),
+                vault_info.clone(),
+                mint_info.clone(),
+                ctx.accounts.weighted_pool.to_account_info(),
+                &[b"vault", pool_key.as_ref(), mint_info.key.as_ref(), &[vault_bump]],
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_2022_program.to_account_info(),
+            )?;
+            
+            assets.push(WeightedAsset {
+                mint: mint_info.key(),
+                vault: vault_key,
+                weight: *weight,
+                balance: 0,
+                hook_program: transfer_info.hook_program_id,
+                flags: transfer_info.flags,
+            });
+        }
+        
+        let weighted_pool = &mut ctx.accounts.weighted_pool;
+        weighted_pool.pool_id = pool_id;
+        weighted_pool.creator = ctx.accounts.creator.key();
+        weighted_pool.created_at = Clock::get()?.unix_timestamp;
+        weighted_pool.fee_rate = fee_rate;
+        weighted_pool.bump = ctx.bumps.weighted_pool;
+        weighted_pool.lp_mint = ctx.accounts.lp_mint.key();
+        weighted_pool.lp_token_supply = 0;
+        weighted_pool.paused = false;
+        weighted_pool.emergency_mode = false;
+        weighted_pool.assets = assets;
+        
+        let amm_config = &mut ctx.accounts.amm_config;
+        amm_config.total_pools += 1;
+        
+        msg!("⚖️ Weighted pool created with {} assets", weighted_pool.assets.len());
+        for asset in &weighted_pool.assets {
+            msg!("🪙 {} weight {} (hook: {:?})", asset.mint, asset.weight, asset.hook_program);
+        }
+        msg!("🎟️ LP mint: {}", weighted_pool.lp_mint);
+        msg!("💸 Fee tier: {}bp", fee_rate);
+        Ok(())
+    }
 
-            let transfer_out_ix = transfer_checked(
-                &spl_token_2022::id(),
-                &ctx.accounts.pool_token_a.key(),
-                &ctx.accounts.token_a_mint.key(),
-                &ctx.accounts.user_token_out.key(),
-                &ctx.accounts.pool.key(),
+    /// Join a weighted pool with every asset in proportion, for exactly `lp_amount` LP tokens.
+    ///
+    /// The first join deposits `max_amounts_in` in full and `lp_amount` is the minimum
+    /// to mint. `remaining_accounts` holds `[mint, vault, user_token]` per asset in pool
+    /// order, then each hooked mint's hook group in the same order.
+    pub fn join_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedJoinExit<'info>>,
+        lp_amount: u64,
+        max_amounts_in: Vec<u64>,
+    ) -> Result<u64> {
+        ctx.accounts.amm_config.require_not_paused()?;
+        ctx.accounts.weighted_pool.require_not_paused()?;
+        let asset_count = ctx.accounts.weighted_pool.assets.len();
+        require!(max_amounts_in.len() == asset_count, AMMError::InvalidAssetCount);
+        let (asset_accounts, mut remaining) = ctx.accounts.split_asset_accounts(ctx.remaining_accounts)?;
+        
+        // Validate the mints' current hooks (a mint authority can change its hook program)
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        for (i, accounts) in asset_accounts.chunks(WEIGHTED_ASSET_ACCOUNTS).enumerate() {
+            let hook_program = validate_transfer_hooks_real(&accounts[0], &ctx.accounts.amm_config, hook_registry)?;
+            ctx.accounts.weighted_pool.assets[i].refresh_hook_program(hook_program);
+        }
+        
+        let pool = &ctx.accounts.weighted_pool;
+        let is_initial_join = pool.lp_token_supply == 0;
+        let mut amounts_paid = Vec::with_capacity(asset_count);
+        let mut amounts_received = Vec::with_capacity(asset_count);
+        for ((asset, accounts), max_amount_in) in pool.assets.iter().zip(asset_accounts.chunks(WEIGHTED_ASSET_ACCOUNTS)).zip(&max_amounts_in) {
+            let mint_info = &accounts[0];
+            let (paid, received) = if is_initial_join {
+                // Transfer fees are withheld in the vault; credit only what arrived
+                let transfer_fee = if asset.has_transfer_fee() { get_transfer_fee(mint_info, *max_amount_in)? } else { 0 };
+                (*max_amount_in, max_amount_in.checked_sub(transfer_fee).ok_or(AMMError::MathOverflow)?)
+            } else {
+                // Proportional share rounded up, grossed up so it arrives in full
+                let needed = math::mul_div_ceil(asset.balance, lp_amount, pool.lp_token_supply)?;
+                let paid = if asset.has_transfer_fee() { get_pre_transfer_fee_amount(mint_info, needed)? } else { needed };
+                require!(paid <= *max_amount_in, AMMError::SlippageExceeded);
+                (paid, needed)
+            };
+            amounts_paid.push(paid);
+            amounts_received.push(received);
+        }
+        
+        let lp_tokens = if is_initial_join {
+            let lp_tokens = weighted_math::initial_lp_tokens(&amounts_received, &pool.weights())?;
+            require!(lp_tokens >= lp_amount, AMMError::InsufficientLPTokens);
+            lp_tokens
+        } else {
+            require!(lp_amount > 0, AMMError::InsufficientLPTokens);
+            lp_amount
+        };
+        
+        for (accounts, amount) in asset_accounts.chunks(WEIGHTED_ASSET_ACCOUNTS).zip(&amounts_paid) {
+            let [mint_info, vault_info, user_token_info] = accounts else {
+                return Err(AMMError::InvalidAssetCount.into());
+            };
+            let hook_accounts = take_hook_accounts(mint_info, &mut remaining)?;
+            transfer_checked_with_hooks(
+                user_token_info.clone(),
+                mint_info.clone(),
+                vault_info.clone(),
+                ctx.accounts.user.to_account_info(),
+                hook_accounts,
+                *amount,
+                get_mint_decimals(mint_info)?,
                 &[],
-                amount_out,
-                token_a_decimals,
             )?;
+        }
+        
+        let pool = &mut ctx.accounts.weighted_pool;
+        for (asset, received) in pool.assets.iter_mut().zip(&amounts_received) {
+            asset.balance = asset.balance.checked_add(*received).ok_or(AMMError::MathOverflow)?;
+        }
+        // Locked minimum liquidity counts towards supply but has no owner
+        if is_initial_join {
+            pool.lp_token_supply = MINIMUM_LIQUIDITY;
+        }
+        pool.lp_token_supply = pool.lp_token_supply.checked_add(lp_tokens)
+            .ok_or(AMMError::MathOverflow)?;
+        
+        ctx.accounts.mint_lp(lp_tokens)?;
+        
+        msg!("✅ Weighted join: {} LP tokens minted for {:?}", lp_tokens, amounts_paid);
+        Ok(lp_tokens)
+    }
+
+    /// Exit a weighted pool into every asset pro rata; slippage limits apply after transfer fees.
+    ///
+    /// Account layout in `remaining_accounts` is the same as for `join_weighted_pool`.
+    pub fn exit_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedJoinExit<'info>>,
+        lp_amount: u64,
+        min_amounts_out: Vec<u64>,
+    ) -> Result<()> {
+        let skip_assets = vec![false; ctx.accounts.weighted_pool.assets.len()];
+        ctx.accounts.exit(ctx.remaining_accounts, lp_amount, &min_amounts_out, &skip_assets)?;
+        Ok(())
+    }
+
+    /// Exit a weighted pool in emergency mode, leaving out assets whose hook program
+    /// is failing. A skipped asset's share leaves the pool and is recorded in the
+    /// caller's `emergency_claim`, to be paid by `claim_emergency_exit_weighted`; its
+    /// `[mint, vault, user_token]` accounts are still passed but its hook group is not.
+    pub fn emergency_exit_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedJoinExit<'info>>,
+        lp_amount: u64,
+        min_amounts_out: Vec<u64>,
+        skip_assets: Vec<bool>,
+    ) -> Result<()> {
+        require!(ctx.accounts.weighted_pool.emergency_mode, AMMError::NotInEmergencyMode);
+        require!(
+            skip_assets.len() == ctx.accounts.weighted_pool.assets.len(),
+            AMMError::InvalidAssetCount
+        );
+        require!(skip_assets.iter().any(|skip| !skip), AMMError::InvalidAmount);
+        
+        msg!("🚨 Emergency weighted exit: {} LP tokens, skipping {:?}", lp_amount, skip_assets);
+        
+        let amounts_out = ctx.accounts.exit(ctx.remaining_accounts, lp_amount, &min_amounts_out, &skip_assets)?;
+        
+        emit!(EmergencyWeightedExitEvent {
+            pool: ctx.accounts.weighted_pool.key(),
+            user: ctx.accounts.user.key(),
+            lp_amount,
+            amounts_out,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Pay out what `emergency_exit_weighted_pool` left owed, once the skipped assets'
+    /// hook programs work again. Accounts as for exits; assets with nothing owed are
+    /// skipped and need no hook group.
+    pub fn claim_emergency_exit_weighted<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedJoinExit<'info>>,
+        skip_assets: Vec<bool>,
+    ) -> Result<()> {
+        require!(
+            skip_assets.len() == ctx.accounts.weighted_pool.assets.len(),
+            AMMError::InvalidAssetCount
+        );
+        let (asset_accounts, remaining) = ctx.accounts.split_asset_accounts(ctx.remaining_accounts)?;
+        
+        let claim = ctx.accounts.emergency_claim.as_mut()
+            .ok_or(AMMError::EmergencyClaimRequired)?;
+        let amounts = claim.take(&skip_assets);
+        require!(amounts.iter().any(|amount| *amount > 0), AMMError::NothingToClaim);
+        
+        let skip_empty: Vec<bool> = amounts.iter().map(|amount| *amount == 0).collect();
+        ctx.accounts.pay_out(asset_accounts, remaining, &amounts, &skip_empty)?;
+        
+        msg!("🧾 Weighted emergency claim paid: {:?}", amounts);
+        
+        emit!(EmergencyClaimPaidEvent {
+            pool: ctx.accounts.weighted_pool.key(),
+            user: ctx.accounts.user.key(),
+            amounts,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Join a weighted pool with a single asset
+    pub fn join_weighted_pool_single<'info>(
+        ctx: Context<'_, '_, '_, 'info, WeightedSingle<'info>>,
+        amount_in: u64,
+        min_lp_out: u64,
+    ) -> Result<u64> {
+        require!(amount_in > 0, AMMError::InvalidAmount);
+        ctx.accounts.amm_config.require_not_paused()?;
+        ctx.accounts.weighted_pool.require_not_paused()?;
+        let index = ctx.accounts.asset_index()?;
+        
+        // Validate the mint's current hook (a mint authority can change its hook program)
+        let hook_program = validate_transfer_hooks_real(&ctx.accounts.mint, &ctx.accounts.amm_config, ctx.accounts.hook_registry.as_ref())?;
+        ctx.accounts.weighted_pool.assets[index].refresh_hook_program(hook_program);
+        
+        let pool = &ctx.accounts.weighted_pool;
+        let asset = &pool.assets[index];
+        let transfer_fee = if asset.has_transfer_fee() { get_transfer_fee(&ctx.accounts.mint, amount_in)? } else { 0 };
+        let amount_received = amount_in.checked_sub(transfer_fee).ok_or(AMMError::MathOverflow)?;
+        let lp_tokens = weighted_math::single_asset_join_lp_tokens(
+            amount_received,
+            asset.balance,
+            asset.weight,
+            pool.lp_token_supply,
+            pool.fee_rate,
+        )?;
+        require!(lp_tokens > 0 && lp_tokens >= min_lp_out, AMMError::InsufficientLPTokens);
+        
+        // Hook accounts in remaining_accounts: the asset's group
+        let mut remaining = ctx.remaining_accounts;
+        let hook_accounts = take_hook_accounts(&ctx.accounts.mint, &mut remaining)?;
+        transfer_checked_with_hooks(
+            ctx.accounts.user_token.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.pool_vault.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            hook_accounts,
+            amount_in,
+            get_mint_decimals(&ctx.accounts.mint)?,
+            &[],
+        )?;
+        
+        let pool = &mut ctx.accounts.weighted_pool;
+        let asset = &mut pool.assets[index];
+        asset.balance = asset.balance.checked_add(amount_received).ok_or(AMMError::MathOverflow)?;
+        pool.lp_token_supply = pool.lp_token_supply.checked_add(lp_tokens)
+            .ok_or(AMMError::MathOverflow)?;
+        
+        ctx.accounts.mint_lp(lp_tokens)?;
+        
+        msg!("✅ Weighted single-asset join: {} LP tokens minted for {} {}", lp_tokens, amount_in, ctx.accounts.mint.key());
+        Ok(lp_tokens)
+    }
+
+    /// Exit a weighted pool into a single asset; slippage limit applies after transfer fees
+    pub fn exit_weighted_pool_single<'info>(
+        ctx: Context<'_, '_, '_, 'info, WeightedSingle<'info>>,
+        lp_amount: u64,
+        min_amount_out: u64,
+    ) -> Result<u64> {
+        require!(lp_amount > 0, AMMError::InsufficientLPTokens);
+        require!(
+            ctx.accounts.user_lp_token.amount >= lp_amount,
+            AMMError::InsufficientLPTokens
+        );
+        let index = ctx.accounts.asset_index()?;
+        
+        // No registry check here: LPs can always withdraw, even if a hook was revoked
+        
+        let pool = &ctx.accounts.weighted_pool;
+        let asset = &pool.assets[index];
+        let amount_out = weighted_math::single_asset_exit_amount(
+            lp_amount,
+            asset.balance,
+            asset.weight,
+            pool.lp_token_supply,
+            pool.fee_rate,
+        )?;
+        let net_amount_out = if asset.has_transfer_fee() {
+            amount_out.checked_sub(get_transfer_fee(&ctx.accounts.mint, amount_out)?)
+                .ok_or(AMMError::MathOverflow)?
+        } else {
+            amount_out
+        };
+        require!(amount_out > 0 && net_amount_out >= min_amount_out, AMMError::SlippageExceeded);
+        
+        let pool_seeds = &[b"weighted_pool", pool.pool_id.as_ref(), &[pool.bump]];
+        let pool_signer = &[&pool_seeds[..]];
+        
+        // Hook accounts in remaining_accounts: the asset's group
+        let mut remaining = ctx.remaining_accounts;
+        let hook_accounts = take_hook_accounts(&ctx.accounts.mint, &mut remaining)?;
+        transfer_checked_with_hooks(
+            ctx.accounts.pool_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.user_token.to_account_info(),
+            ctx.accounts.weighted_pool.to_account_info(),
+            hook_accounts,
+            amount_out,
+            get_mint_decimals(&ctx.accounts.mint)?,
+            pool_signer,
+        )?;
+        
+        ctx.accounts.burn_lp(lp_amount)?;
+        
+        let pool = &mut ctx.accounts.weighted_pool;
+        let asset = &mut pool.assets[index];
+        asset.balance = asset.balance.checked_sub(amount_out).ok_or(AMMError::MathOverflow)?;
+        pool.lp_token_supply = pool.lp_token_supply.checked_sub(lp_amount)
+            .ok_or(AMMError::MathOverflow)?;
+        
+        msg!("✅ Weighted single-asset exit: {} {} for {} LP tokens", net_amount_out, ctx.accounts.mint.key(), lp_amount);
+        Ok(net_amount_out)
+    }
+
+    /// Swap between any two assets of a weighted pool
+    pub fn swap_weighted<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapWeighted<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<u64> {
+        require!(amount_in > 0, AMMError::InvalidAmount);
+        ctx.accounts.amm_config.require_not_paused()?;
+        ctx.accounts.weighted_pool.require_not_paused()?;
+        let pool = &ctx.accounts.weighted_pool;
+        let index_in = pool.asset_index(&ctx.accounts.mint_in.key())?;
+        let index_out = pool.asset_index(&ctx.accounts.mint_out.key())?;
+        require!(index_in != index_out, AMMError::InvalidTokenPair);
+        require_keys_eq!(ctx.accounts.pool_vault_in.key(), pool.assets[index_in].vault, AMMError::InvalidVault);
+        require_keys_eq!(ctx.accounts.pool_vault_out.key(), pool.assets[index_out].vault, AMMError::InvalidVault);
+        
+        msg!("🔄 Weighted swap: {} {} → {}", amount_in, ctx.accounts.mint_in.key(), ctx.accounts.mint_out.key());
+        
+        // Validate the mints' current hooks (a mint authority can change its hook program)
+        let hook_registry = ctx.accounts.hook_registry.as_ref();
+        let hook_in = validate_transfer_hooks_real(&ctx.accounts.mint_in, &ctx.accounts.amm_config, hook_registry)?;
+        let hook_out = validate_transfer_hooks_real(&ctx.accounts.mint_out, &ctx.accounts.amm_config, hook_registry)?;
+        let pool = &mut ctx.accounts.weighted_pool;
+        pool.assets[index_in].refresh_hook_program(hook_in);
+        pool.assets[index_out].refresh_hook_program(hook_out);
+        
+        let pool = &ctx.accounts.weighted_pool;
+        let (asset_in, asset_out) = (&pool.assets[index_in], &pool.assets[index_out]);
+        let transfer_fee_in = if asset_in.has_transfer_fee() { get_transfer_fee(&ctx.accounts.mint_in, amount_in)? } else { 0 };
+        let amount_received = amount_in.checked_sub(transfer_fee_in).ok_or(AMMError::MathOverflow)?;
+        let quote = weighted_math::swap_exact_in(
+            amount_received,
+            asset_in.balance,
+            asset_in.weight,
+            asset_out.balance,
+            asset_out.weight,
+            pool.fee_rate,
+        )?;
+        let transfer_fee_out = if asset_out.has_transfer_fee() {
+            get_transfer_fee(&ctx.accounts.mint_out, quote.amount_out)?
+        } else {
+            0
+        };
+        let amount_out = quote.amount_out.checked_sub(transfer_fee_out).ok_or(AMMError::MathOverflow)?;
+        require!(amount_out >= minimum_amount_out, AMMError::InsufficientOutput);
+        
+        // Hook accounts in remaining_accounts: input mint group, then output mint group
+        let mut remaining = ctx.remaining_accounts;
+        let hook_accounts_in = take_hook_accounts(&ctx.accounts.mint_in, &mut remaining)?;
+        let hook_accounts_out = take_hook_accounts(&ctx.accounts.mint_out, &mut remaining)?;
+        
+        transfer_checked_with_hooks(
+            ctx.accounts.user_token_in.to_account_info(),
+            ctx.accounts.mint_in.to_account_info(),
+            ctx.accounts.pool_vault_in.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            hook_accounts_in,
+            amount_in,
+            get_mint_decimals(&ctx.accounts.mint_in)?,
+            &[],
+        )?;
+        
+        let pool_seeds = &[b"weighted_pool", pool.pool_id.as_ref(), &[pool.bump]];
+        let pool_signer = &[&pool_seeds[..]];
+        transfer_checked_with_hooks(
+            ctx.accounts.pool_vault_out.to_account_info(),
+            ctx.accounts.mint_out.to_account_info(),
+            ctx.accounts.user_token_out.to_account_info(),
+            ctx.accounts.weighted_pool.to_account_info(),
+            hook_accounts_out,
+            quote.amount_out,
+            get_mint_decimals(&ctx.accounts.mint_out)?,
+            pool_signer,
+        )?;
+        
+        let pool = &mut ctx.accounts.weighted_pool;
+        pool.assets[index_in].balance = pool.assets[index_in].balance.checked_add(amount_received)
+            .ok_or(AMMError::MathOverflow)?;
+        pool.assets[index_out].balance = pool.assets[index_out].balance.checked_sub(quote.amount_out)
+            .ok_or(AMMError::MathOverflow)?;
+        
+        msg!("💰 Weighted swap completed: {} output (fee: {})", amount_out, quote.fee_amount);
+        Ok(amount_out)
+    }
+
+    /// Get pool information
+    pub fn get_pool_info(ctx: Context<GetPoolInfo>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        
+        msg!("📋 Pool Information:");
+        msg!("🪙 Token A: {}", pool.token_a_mint);
+        msg!("🪙 Token B: {}", pool.token_b_mint);
+        msg!("💰 Liquidity A: {}", pool.total_liquidity_a);
+        msg!("💰 Liquidity B: {}", pool.total_liquidity_b);
+        msg!("🏷️  LP Supply: {}", pool.lp_token_supply);
+        msg!("💱 Current Price: {}", pool.current_price);
+        msg!("💸 Fee Rate: {}bp", pool.fee_rate);
+        msg!("📈 Pool Type: {:?} (amp: {})", pool.pool_type, pool.current_amp(Clock::get()?.unix_timestamp));
+        msg!("🏦 Protocol Fees: A={}, B={}", pool.protocol_fees_a, pool.protocol_fees_b);
+        msg!("🔗 Hook Enabled: {}", pool.hook_enabled);
+        msg!("🔗 Hook Programs: A={:?}, B={:?}", pool.token_a_hook_program, pool.token_b_hook_program);
+        msg!("🏷️  Extension Flags: A={:#04x}, B={:#04x}", pool.token_a_flags, pool.token_b_flags);
+        
+        Ok(())
+    }
+}
+
+/// Helper function to get mint decimals
+fn get_mint_decimals(mint_account: &AccountInfo) -> Result<u8> {
+    let mint_data = mint_account.try_borrow_data()?;
+    
+    // Try to parse as StateWithExtensions first (for Token-2022 with extensions)
+    match StateWithExtensions::<Token2022Mint>::unpack(&mint_data) {
+        Ok(mint_with_extensions) => {
+            Ok(mint_with_extensions.base.decimals)
+        }
+        Err(_) => {
+            // Fallback to basic Token-2022 mint
+            match Token2022Mint::unpack(&mint_data) {
+                Ok(mint) => Ok(mint.decimals),
+                Err(_) => {
+                    msg!("❌ Failed to parse mint data");
+                    Err(AMMError::InvalidTokenProgram.into())
+                }
+            }
+        }
+    }
+}
+
+/// Transfer-affecting extensions found on a Token-2022 mint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintTransferInfo {
+    pub hook_program_id: Option<Pubkey>,
+    pub flags: u8, // MINT_FLAG_* bits
+}
+
+/// Helper function to read a mint's transfer hook and transfer-affecting extensions
+pub fn read_mint_transfer_info(mint_account: &AccountInfo) -> Result<MintTransferInfo> {
+    let mint_data = mint_account.try_borrow_data()?;
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)
+        .map_err(|_| AMMError::InvalidTokenProgram)?;
+    
+    let mut flags = 0u8;
+    for extension in mint.get_extension_types()? {
+        flags |= match extension {
+            ExtensionType::TransferHook => MINT_FLAG_TRANSFER_HOOK,
+            ExtensionType::TransferFeeConfig => MINT_FLAG_TRANSFER_FEE,
+            ExtensionType::PermanentDelegate => MINT_FLAG_PERMANENT_DELEGATE,
+            ExtensionType::NonTransferable => MINT_FLAG_NON_TRANSFERABLE,
+            _ => 0,
+        };
+    }
+    
+    Ok(MintTransferInfo {
+        hook_program_id: transfer_hook::get_program_id(&mint),
+        flags,
+    })
+}
+
+/// Helper function to price a swap on a pool, including swap, protocol and transfer fees
+#[allow(clippy::too_many_arguments)]
+fn quote_swap_amounts(
+    pool: &Pool,
+    protocol_fee_rate: u64,
+    mint_in_info: &AccountInfo,
+    mint_out_info: &AccountInfo,
+    a_to_b: bool,
+    mode: SwapMode,
+    amount: u64,
+) -> Result<SwapQuoteResult> {
+    require!(amount > 0, AMMError::InvalidAmount);
+    
+    let (fee_in, fee_out) = pool.transfer_fee_sides(a_to_b);
+    let (reserve_in, reserve_out) = pool.reserves(a_to_b);
+    
+    let (amount_in, transfer_fee_in, fee_amount, pool_amount_out) = match mode {
+        SwapMode::ExactIn => {
+            // Token-2022 transfer fees are withheld in the vault, so only the net amount reaches the reserves
+            let transfer_fee_in = if fee_in { get_transfer_fee(mint_in_info, amount)? } else { 0 };
+            let amount_received = amount.checked_sub(transfer_fee_in)
+                .ok_or(AMMError::MathOverflow)?;
+            let math::SwapQuote { fee_amount, amount_out } = match pool.pool_type {
+                PoolType::ConstantProduct => {
+                    math::swap_exact_in(amount_received, reserve_in, reserve_out, pool.fee_rate)?
+                }
+                PoolType::StableSwap => stable_math::swap_exact_in(
+                    amount_received,
+                    reserve_in,
+                    reserve_out,
+                    pool.fee_rate,
+                    pool.current_amp(Clock::get()?.unix_timestamp),
+                )?,
+            };
+            (amount, transfer_fee_in, fee_amount, amount_out)
+        }
+        SwapMode::ExactOut => {
+            // Gross payout so that `amount` survives the output mint's transfer fee
+            let pool_amount_out = if fee_out {
+                get_pre_transfer_fee_amount(mint_out_info, amount)?
+            } else {
+                amount
+            };
+            let math::ExactOutQuote { amount_in: required_received, fee_amount } = match pool.pool_type {
+                PoolType::ConstantProduct => {
+                    math::swap_exact_out(pool_amount_out, reserve_in, reserve_out, pool.fee_rate)?
+                }
+                PoolType::StableSwap => stable_math::swap_exact_out(
+                    pool_amount_out,
+                    reserve_in,
+                    reserve_out,
+                    pool.fee_rate,
+                    pool.current_amp(Clock::get()?.unix_timestamp),
+                )?,
+            };
+            // Gross input so that `required_received` survives the input mint's transfer fee
+            let amount_in = if fee_in {
+                get_pre_transfer_fee_amount(mint_in_info, required_received)?
+            } else {
+                required_received
+            };
+            let transfer_fee_in = if fee_in { get_transfer_fee(mint_in_info, amount_in)? } else { 0 };
+            (amount_in, transfer_fee_in, fee_amount, pool_amount_out)
+        }
+    };
+    
+    let amount_received = amount_in.checked_sub(transfer_fee_in).ok_or(AMMError::MathOverflow)?;
+    let protocol_fee = math::protocol_fee(fee_amount, protocol_fee_rate)?;
+    
+    // The user receives the payout minus the output mint's transfer fee
+    let transfer_fee_out = if fee_out { get_transfer_fee(mint_out_info, pool_amount_out)? } else { 0 };
+    let amount_out = pool_amount_out.checked_sub(transfer_fee_out).ok_or(AMMError::MathOverflow)?;
+    
+    let new_reserve_in = reserve_in
+        .checked_add(amount_received)
+        .and_then(|v| v.checked_sub(protocol_fee))
+        .ok_or(AMMError::MathOverflow)?;
+    let new_reserve_out = reserve_out.checked_sub(pool_amount_out).ok_or(AMMError::MathOverflow)?;
+    let amount_in_after_fee = amount_received.checked_sub(fee_amount).ok_or(AMMError::MathOverflow)?;
+    let price_impact_bps = match pool.pool_type {
+        PoolType::ConstantProduct => {
+            math::price_impact_bps(amount_in_after_fee, pool_amount_out, reserve_in, reserve_out)?
+        }
+        PoolType::StableSwap => math::price_impact_bps_at_spot(
+            amount_in_after_fee,
+            pool_amount_out,
+            pool.spot_price(a_to_b, Clock::get()?.unix_timestamp)?,
+        )?,
+    };
+    
+    Ok(SwapQuoteResult {
+        amount_in,
+        amount_out,
+        amount_received,
+        pool_amount_out,
+        fee_amount,
+        protocol_fee,
+        transfer_fee_in,
+        transfer_fee_out,
+        new_reserve_in,
+        new_reserve_out,
+        price_impact_bps,
+    })
+}
+
+/// Helper function to compute the Token-2022 transfer fee withheld from `amount` this epoch
+fn get_transfer_fee(mint_account: &AccountInfo, amount: u64) -> Result<u64> {
+    let mint_data = mint_account.try_borrow_data()?;
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)
+        .map_err(|_| AMMError::InvalidTokenProgram)?;
+    
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or_else(|| AMMError::MathOverflow.into()),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Helper function to compute the amount to send so `post_fee_amount` arrives after this epoch's transfer fee
+fn get_pre_transfer_fee_amount(mint_account: &AccountInfo, post_fee_amount: u64) -> Result<u64> {
+    let mint_data = mint_account.try_borrow_data()?;
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)
+        .map_err(|_| AMMError::InvalidTokenProgram)?;
+    
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => fee_config
+            .get_epoch_fee(Clock::get()?.epoch)
+            .calculate_pre_fee_amount(post_fee_amount)
+            // a 100% fee makes any positive amount unreachable
+            .filter(|pre_fee_amount| *pre_fee_amount >= post_fee_amount)
+            .ok_or_else(|| AMMError::MathOverflow.into()),
+        Err(_) => Ok(post_fee_amount),
+    }
+}
+
+/// Helper function to validate transfer hooks for REAL Token-2022
+///
+/// Returns the mint's current hook program, if any. Hook programs the pauser
+/// has paused are rejected whatever the registry says.
+fn validate_transfer_hooks_real(
+    mint_account: &AccountInfo,
+    amm_config: &AMMConfig,
+    hook_registry: Option<&Account<hook_registry::HookRegistry>>,
+) -> Result<Option<Pubkey>> {
+    let hook_program_id = {
+        let mint_data = mint_account.try_borrow_data()?;
+        let mint = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)
+            .map_err(|_| AMMError::InvalidTokenProgram)?;
+        transfer_hook::get_program_id(&mint)
+    };
+    
+    let Some(hook_program_id) = hook_program_id else {
+        msg!("ℹ️  No transfer hooks found for mint: {}", mint_account.key());
+        return Ok(None);
+    };
+    
+    msg!("🔗 Validating transfer hook {} for mint: {}", hook_program_id, mint_account.key());
+    
+    if amm_config.paused_hook_programs.contains(&hook_program_id) {
+        msg!("⛔ Hook {} is paused", hook_program_id);
+        return Err(error!(AMMError::HookProgramPaused));
+    }
+    
+    // Hooked mints are only accepted once a registry has been configured
+    let Some(registry) = hook_registry else {
+        msg!("❌ No hook registry configured for hooked mint");
+        return Err(error!(AMMError::HookValidationFailed));
+    };
+    
+    // Same rules as hook_registry::is_hook_approved
+    if !registry.is_enabled {
+        msg!("ℹ️  Registry disabled - all hooks considered valid");
+        return Ok(Some(hook_program_id));
+    }
+    
+    let is_approved = registry.approved_hooks.contains(&hook_program_id);
+    let is_active = registry
+        .hook_metadata
+        .iter()
+        .find(|m| m.program_id == hook_program_id)
+        .is_some_and(|m| m.is_active);
+    
+    if !(is_approved && is_active) {
+        msg!("❌ Hook {} not approved: approved={}, active={}",
+             hook_program_id, is_approved, is_active);
+        return Err(error!(AMMError::HookValidationFailed)
+            .with_pubkeys((hook_program_id, registry.key())));
+    }
+    
+    msg!("✅ Transfer hook validation passed");
+    Ok(Some(hook_program_id))
+}
+
+/// Split the accounts a mint's transfer hook needs off the front of `remaining`.
+///
+/// For a hooked mint the group is `[hook_program, validation_pda, extra accounts...]`,
+/// with the number of extras taken from its ExtraAccountMetaList. Mints
+/// without a hook consume nothing.
+fn take_hook_accounts<'a, 'info>(
+    mint_account: &AccountInfo<'info>,
+    remaining: &mut &'a [AccountInfo<'info>],
+) -> Result<&'a [AccountInfo<'info>]> {
+    let hook_program_id = {
+        let mint_data = mint_account.try_borrow_data()?;
+        let mint = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)
+            .map_err(|_| AMMError::InvalidTokenProgram)?;
+        transfer_hook::get_program_id(&mint)
+    };
+    
+    let Some(hook_program_id) = hook_program_id else {
+        return Ok(&[]);
+    };
+    
+    let validation_pda = get_extra_account_metas_address(mint_account.key, &hook_program_id);
+    if remaining.len() < 2
+        || remaining[0].key != &hook_program_id
+        || remaining[1].key != &validation_pda
+    {
+        msg!("❌ Missing hook accounts for mint {} (hook: {})", mint_account.key(), hook_program_id);
+        return Err(AMMError::MissingHookAccounts.into());
+    }
+    
+    let extra_count = {
+        let data = remaining[1].try_borrow_data()?;
+        let tlv_state = TlvStateBorrowed::unpack(&data)?;
+        ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&tlv_state)?
+            .data()
+            .len()
+    };
+    
+    let group_len = 2 + extra_count;
+    require!(remaining.len() >= group_len, AMMError::MissingHookAccounts);
+    
+    let (group, rest) = remaining.split_at(group_len);
+    *remaining = rest;
+    Ok(group)
+}
+
+/// Hook-aware `transfer_checked` CPI: if the mint has a transfer hook, its
+/// ExtraAccountMetaList accounts are resolved out of `extra_accounts`
+#[allow(clippy::too_many_arguments)]
+fn transfer_checked_with_hooks<'info>(
+    source: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    extra_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    
+    invoke_transfer_checked(
+        &spl_token_2022::id(),
+        source,
+        mint,
+        destination,
+        authority,
+        extra_accounts,
+        amount,
+        decimals,
+        signer_seeds,
+    )?;
+    
+    Ok(())
+}
+
+/// Create a pool vault at a PDA, sized for the account extensions its mint requires
+fn create_pool_vault<'info>(
+    payer: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    pool: AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let space = {
+        let mint_data = mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)
+            .map_err(|_| AMMError::InvalidTokenProgram)?;
+        let account_extensions =
+            ExtensionType::get_required_init_account_extensions(&mint_state.get_extension_types()?);
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&account_extensions)?
+    };
+    
+    anchor_lang::system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program,
+            anchor_lang::system_program::CreateAccount {
+                from: payer,
+                to: vault.clone(),
+            },
+            &[vault_seeds],
+        ),
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        &spl_token_2022::id(),
+    )?;
+    
+    token_interface::initialize_account3(CpiContext::new(
+        token_program,
+        token_interface::InitializeAccount3 {
+            account: vault.clone(),
+            mint,
+            authority: pool,
+        },
+    ))?;
+    
+    // A frozen DefaultAccountState would leave the vault unusable
+    let vault_data = vault.try_borrow_data()?;
+    let vault_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&vault_data)?;
+    require!(!vault_state.base.is_frozen(), AMMError::UnsupportedMintExtension);
+    Ok(())
+}
+
+// ========== ACCOUNT STRUCTURES ==========
+
+#[derive(Accounts)]
+pub struct InitializeAMM<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AMMConfig::SPACE,
+        seeds = [b"amm_config"],
+        bump
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetHookRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump,
+        has_one = registry_manager @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    pub registry_manager: Signer<'info>,
+}
+
+/// Owner-only config changes
+#[derive(Accounts)]
+pub struct UpdateAMMConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump,
+        has_one = authority @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump,
+        constraint = amm_config.pending_authority == Some(new_authority.key()) @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePauseConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump,
+        has_one = pauser @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    pub pauser: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPoolStatus<'info> {
+    #[account(
+        seeds = [b"amm_config"],
+        bump,
+        has_one = pauser @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    pub pauser: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClPoolStatus<'info> {
+    #[account(
+        seeds = [b"amm_config"],
+        bump,
+        has_one = pauser @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    #[account(mut)]
+    pub cl_pool: Account<'info, ClPool>,
+    
+    pub pauser: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWeightedPoolStatus<'info> {
+    #[account(
+        seeds = [b"amm_config"],
+        bump,
+        has_one = pauser @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    #[account(mut)]
+    pub weighted_pool: Box<Account<'info, WeightedPool>>,
+    
+    pub pauser: Signer<'info>,
+}
+
+/// Fee tier and protocol fee changes
+#[derive(Accounts)]
+pub struct UpdateFeeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump,
+        has_one = fee_manager @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    pub fee_manager: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(initial_price: u64, fee_rate: u64)]
+pub struct CreatePool<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Pool::SPACE,
+        seeds = [
+            b"pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            fee_rate.to_le_bytes().as_ref()
+        ],
+        bump,
+        // One pool per pair (and tier): mints distinct and sorted (token_a < token_b).
+        // Checked on this init field, which Anchor runs before the vault inits, so
+        // identical mints fail here rather than on the duplicate vault address
+        constraint = token_a_mint.key() < token_b_mint.key() @ AMMError::InvalidTokenPair
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(mut)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// CHECK: Token A mint (Token-2022)
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint (Token-2022)
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// Pool-owned vault for token A
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"vault", pool.key().as_ref(), token_a_mint.key().as_ref()],
+        bump,
+        token::mint = token_a_mint,
+        token::authority = pool,
+        token::token_program = token_2022_program,
+    )]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Pool-owned vault for token B
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"vault", pool.key().as_ref(), token_b_mint.key().as_ref()],
+        bump,
+        token::mint = token_b_mint,
+        token::authority = pool,
+        token::token_program = token_2022_program,
+    )]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"lp_mint", pool.key().as_ref()],
+        bump,
+        mint::decimals = LP_DECIMALS,
+        mint::authority = pool,
+        mint::token_program = token_2022_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            pool.fee_rate.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// CHECK: Token A mint
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// User's Token A account
+    #[account(
+        mut,
+        token::mint = token_a_mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// User's Token B account
+    #[account(
+        mut,
+        token::mint = token_b_mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: Pool's Token A vault
+    #[account(mut, address = pool.token_a_vault @ AMMError::InvalidVault)]
+    pub pool_token_a: UncheckedAccount<'info>,
+    /// CHECK: Pool's Token B vault
+    #[account(mut, address = pool.token_b_vault @ AMMError::InvalidVault)]
+    pub pool_token_b: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = pool.lp_mint,
+        mint::token_program = token_2022_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// Receives the minted LP tokens
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub user_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Optional price observation ring buffer, updated when present
+    #[account(
+        mut,
+        seeds = [b"observations", pool.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Option<Account<'info, Observations>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            pool.fee_rate.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// CHECK: Token A mint
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// User's Token A account
+    #[account(
+        mut,
+        token::mint = token_a_mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// User's Token B account
+    #[account(
+        mut,
+        token::mint = token_b_mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: Pool's Token A vault
+    #[account(mut, address = pool.token_a_vault @ AMMError::InvalidVault)]
+    pub pool_token_a: UncheckedAccount<'info>,
+    /// CHECK: Pool's Token B vault
+    #[account(mut, address = pool.token_b_vault @ AMMError::InvalidVault)]
+    pub pool_token_b: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = pool.lp_mint,
+        mint::token_program = token_2022_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// LP tokens to burn
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Optional price observation ring buffer, updated when present
+    #[account(
+        mut,
+        seeds = [b"observations", pool.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Option<Account<'info, Observations>>,
+    
+    /// Records what an emergency withdrawal skips; required to skip a side or claim
+    #[account(
+        mut,
+        seeds = [b"emergency_claim", pool.key().as_ref(), user.key().as_ref()],
+        bump = emergency_claim.bump
+    )]
+    pub emergency_claim: Option<Account<'info, EmergencyClaim>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> RemoveLiquidity<'info> {
+    /// Burn `lp_amount` for its share of the reserves, paying out every side not
+    /// skipped and recording the rest in `emergency_claim`. Returns the amounts sent
+    /// before transfer fees.
+    fn withdraw(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        skip_a: bool,
+        skip_b: bool,
+    ) -> Result<(u64, u64)> {
+        let pool = &self.pool;
+        
+        require!(lp_amount > 0, AMMError::InsufficientLPTokens);
+        require!(
+            self.user_lp_token.amount >= lp_amount,
+            AMMError::InsufficientLPTokens
+        );
+        require!(pool.lp_token_supply > 0, AMMError::InsufficientLiquidity);
+        
+        // Pro-rata share of both reserves (rounded down in favour of the pool); a
+        // balanced exit leaves either invariant's price unchanged
+        let (share_a, share_b) = match pool.pool_type {
+            PoolType::ConstantProduct | PoolType::StableSwap => (
+                math::withdraw_amount(lp_amount, pool.total_liquidity_a, pool.lp_token_supply)?,
+                math::withdraw_amount(lp_amount, pool.total_liquidity_b, pool.lp_token_supply)?,
+            ),
+        };
+        let amount_a = if skip_a { 0 } else { share_a };
+        let amount_b = if skip_b { 0 } else { share_b };
+        
+        // Slippage limits apply to what the provider receives after transfer fees
+        let net_amount_a = if pool.token_a_has_transfer_fee() {
+            amount_a.checked_sub(get_transfer_fee(&self.token_a_mint, amount_a)?)
+                .ok_or(AMMError::MathOverflow)?
+        } else {
+            amount_a
+        };
+        let net_amount_b = if pool.token_b_has_transfer_fee() {
+            amount_b.checked_sub(get_transfer_fee(&self.token_b_mint, amount_b)?)
+                .ok_or(AMMError::MathOverflow)?
+        } else {
+            amount_b
+        };
+        
+        require!(net_amount_a >= min_amount_a, AMMError::SlippageExceeded);
+        require!(net_amount_b >= min_amount_b, AMMError::SlippageExceeded);
+        require!(amount_a > 0 || amount_b > 0, AMMError::InsufficientLiquidity);
+        
+        // A skipped side's share leaves the reserves and is owed to the provider
+        let (owed_a, owed_b) = (share_a - amount_a, share_b - amount_b);
+        if owed_a > 0 || owed_b > 0 {
+            self.emergency_claim.as_mut()
+                .ok_or(AMMError::EmergencyClaimRequired)?
+                .add(&[owed_a, owed_b])?;
+        }
+        
+        // No registry check here: LPs can always withdraw, even if a hook was revoked
+        
+        self.pay_out(remaining_accounts, amount_a, amount_b, skip_a, skip_b)?;
+        
+        // Burn the LP share and update pool state
+        token_interface::burn(
+            CpiContext::new(
+                self.token_2022_program.to_account_info(),
+                Burn {
+                    mint: self.lp_mint.to_account_info(),
+                    from: self.user_lp_token.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            lp_amount,
+        )?;
+        
+        let pool = &mut self.pool;
+        pool.accumulate_prices(Clock::get()?.unix_timestamp)?;
+        pool.total_liquidity_a = pool.total_liquidity_a.checked_sub(share_a)
+            .ok_or(AMMError::MathOverflow)?;
+        pool.total_liquidity_b = pool.total_liquidity_b.checked_sub(share_b)
+            .ok_or(AMMError::MathOverflow)?;
+        pool.lp_token_supply = pool.lp_token_supply.checked_sub(lp_amount)
+            .ok_or(AMMError::MathOverflow)?;
+        pool.refresh_current_price();
+        if let Some(observations) = self.observations.as_mut() {
+            observations.record(pool);
+        }
+        
+        msg!("✅ Liquidity removed: {} A, {} B for {} LP tokens", net_amount_a, net_amount_b, lp_amount);
+        msg!("📊 New reserves: A={}, B={}", pool.total_liquidity_a, pool.total_liquidity_b);
+        Ok((amount_a, amount_b))
+    }
+    
+    /// Pool -> user transfers, PDA-signed and hook-aware; a skipped side is not
+    /// transferred and has no hook group in `remaining_accounts`
+    fn pay_out(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        amount_a: u64,
+        amount_b: u64,
+        skip_a: bool,
+        skip_b: bool,
+    ) -> Result<()> {
+        let token_a_decimals = get_mint_decimals(&self.token_a_mint)?;
+        let token_b_decimals = get_mint_decimals(&self.token_b_mint)?;
+        
+        let token_a_key = self.token_a_mint.key();
+        let token_b_key = self.token_b_mint.key();
+        let fee_rate_bytes = self.pool.fee_rate.to_le_bytes();
+        let pool_seeds = &[
+            b"pool",
+            token_a_key.as_ref(),
+            token_b_key.as_ref(),
+            fee_rate_bytes.as_ref(),
+            &[self.pool.bump],
+        ];
+        let pool_signer = &[&pool_seeds[..]];
+        
+        // Hook accounts in remaining_accounts: token A group, then token B group
+        let mut remaining = remaining_accounts;
+        
+        if !skip_a {
+            let hook_accounts_a = take_hook_accounts(&self.token_a_mint, &mut remaining)?;
+            transfer_checked_with_hooks(
+                self.pool_token_a.to_account_info(),
+                self.token_a_mint.to_account_info(),
+                self.user_token_a.to_account_info(),
+                self.pool.to_account_info(),
+                hook_accounts_a,
+                amount_a,
+                token_a_decimals,
+                pool_signer,
+            )?;
+        }
+        
+        if !skip_b {
+            let hook_accounts_b = take_hook_accounts(&self.token_b_mint, &mut remaining)?;
+            transfer_checked_with_hooks(
+                self.pool_token_b.to_account_info(),
+                self.token_b_mint.to_account_info(),
+                self.user_token_b.to_account_info(),
+                self.pool.to_account_info(),
+                hook_accounts_b,
+                amount_b,
+                token_b_decimals,
+                pool_signer,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            pool.fee_rate.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// CHECK: Token A mint
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// User's input token account (its mint selects the swap direction)
+    #[account(mut, token::token_program = token_2022_program)]
+    pub user_token_in: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// User's output token account (mint and owner checked against the direction)
+    #[account(mut, token::token_program = token_2022_program)]
+    pub user_token_out: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: Pool's Token A vault
+    #[account(mut, address = pool.token_a_vault @ AMMError::InvalidVault)]
+    pub pool_token_a: UncheckedAccount<'info>,
+    /// CHECK: Pool's Token B vault
+    #[account(mut, address = pool.token_b_vault @ AMMError::InvalidVault)]
+    pub pool_token_b: UncheckedAccount<'info>,
+    
+    /// Optional price observation ring buffer, updated when present
+    #[account(
+        mut,
+        seeds = [b"observations", pool.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Option<Account<'info, Observations>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> Swap<'info> {
+    /// Check pause state, resolve the direction from the input account, validate hooks and user accounts
+    fn prepare(&mut self, amount_in: u64) -> Result<bool> {
+        self.amm_config.require_not_paused()?;
+        self.pool.require_not_paused()?;
+        
+        let input_mint = self.user_token_in.mint;
+        let a_to_b = if input_mint == self.pool.token_a_mint {
+            true
+        } else if input_mint == self.pool.token_b_mint {
+            false
+        } else {
+            return Err(AMMError::InvalidTokenPair.into());
+        };
+        
+        msg!("🔄 REAL Swap: {} input, direction: {}", 
+             amount_in, if a_to_b { "A→B" } else { "B→A" });
+        
+        // Validate the mints' current hooks (a mint authority can change its hook program)
+        let hook_registry = self.hook_registry.as_ref();
+        let hook_a = validate_transfer_hooks_real(&self.token_a_mint, &self.amm_config, hook_registry)?;
+        let hook_b = validate_transfer_hooks_real(&self.token_b_mint, &self.amm_config, hook_registry)?;
+        self.pool.refresh_hook_programs(hook_a, hook_b);
+        
+        // User accounts must match the swap direction and belong to the user
+        let mint_out = if a_to_b { self.pool.token_b_mint } else { self.pool.token_a_mint };
+        require_keys_eq!(self.user_token_out.mint, mint_out, AMMError::InvalidTokenAccount);
+        require_keys_eq!(self.user_token_in.owner, self.user.key(), AMMError::InvalidTokenAccount);
+        require_keys_eq!(self.user_token_out.owner, self.user.key(), AMMError::InvalidTokenAccount);
+        
+        Ok(a_to_b)
+    }
+    
+    /// (input mint, output mint) for the direction
+    fn mints(&self, a_to_b: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        if a_to_b {
+            (self.token_a_mint.to_account_info(), self.token_b_mint.to_account_info())
+        } else {
+            (self.token_b_mint.to_account_info(), self.token_a_mint.to_account_info())
+        }
+    }
+    
+    /// Move tokens with hook-aware transfers and book the swap on the pool
+    fn settle(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+        a_to_b: bool,
+        quote: &SwapQuoteResult,
+    ) -> Result<()> {
+        let (mint_in_info, mint_out_info) = self.mints(a_to_b);
+        let (vault_in, vault_out) = if a_to_b {
+            (self.pool_token_a.to_account_info(), self.pool_token_b.to_account_info())
+        } else {
+            (self.pool_token_b.to_account_info(), self.pool_token_a.to_account_info())
+        };
+        let decimals_in = get_mint_decimals(&mint_in_info)?;
+        let decimals_out = get_mint_decimals(&mint_out_info)?;
+        
+        // Hook accounts in remaining_accounts: input mint group, then output mint group
+        let mut remaining = remaining_accounts;
+        let hook_accounts_in = take_hook_accounts(&mint_in_info, &mut remaining)?;
+        let hook_accounts_out = take_hook_accounts(&mint_out_info, &mut remaining)?;
+
+        // REAL Token-2022 transfers
+        // Transfer input from user to pool
+        transfer_checked_with_hooks(
+            self.user_token_in.to_account_info(),
+            mint_in_info,
+            vault_in,
+            self.user.to_account_info(),
+            hook_accounts_in,
+            quote.amount_in,
+            decimals_in,
+            &[],
+        )?;
+
+        // Transfer output from pool to user
+        let token_a_key = self.token_a_mint.key();
+        let token_b_key = self.token_b_mint.key();
+        let fee_rate_bytes = self.pool.fee_rate.to_le_bytes();
+        let pool_seeds = &[
+            b"pool",
+            token_a_key.as_ref(),
+            token_b_key.as_ref(),
+            fee_rate_bytes.as_ref(),
+            &[self.pool.bump],
+        ];
+        let pool_signer = &[&pool_seeds[..]];
+
+        transfer_checked_with_hooks(
+            vault_out,
+            mint_out_info,
+            self.user_token_out.to_account_info(),
+            self.pool.to_account_info(),
+            hook_accounts_out,
+            quote.pool_amount_out,
+            decimals_out,
+            pool_signer,
+        )?;
+
+        // Update pool reserves with the amounts that actually moved; the protocol share
+        // of the fee stays in the vault but is accrued outside the LP reserves
+        let pool = &mut self.pool;
+        pool.accumulate_prices(Clock::get()?.unix_timestamp)?;
+        pool.apply_swap(a_to_b, quote.amount_received, quote.pool_amount_out, quote.protocol_fee)?;
+        if let Some(observations) = self.observations.as_mut() {
+            observations.record(pool);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            pool.fee_rate.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// CHECK: Token A mint
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint
+    pub token_b_mint: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// User's account for the first mint of the path
+    #[account(mut, token::token_program = token_2022_program)]
+    pub user_token_in: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolAmp<'info> {
+    #[account(
+        seeds = [b"amm_config"],
+        bump,
+        has_one = authority @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(
+        seeds = [b"amm_config"],
+        bump,
+        has_one = fee_manager @ AMMError::Unauthorized
+    )]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            pool.fee_rate.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// CHECK: Token A mint
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: Pool's Token A vault
+    #[account(mut, address = pool.token_a_vault @ AMMError::InvalidVault)]
+    pub pool_token_a: UncheckedAccount<'info>,
+    /// CHECK: Pool's Token B vault
+    #[account(mut, address = pool.token_b_vault @ AMMError::InvalidVault)]
+    pub pool_token_b: UncheckedAccount<'info>,
+    
+    /// Treasury's Token A account
+    #[account(
+        mut,
+        token::mint = token_a_mint,
+        token::authority = amm_config.treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub treasury_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Treasury's Token B account
+    #[account(
+        mut,
+        token::mint = token_b_mint,
+        token::authority = amm_config.treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub treasury_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub fee_manager: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeObservations<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Observations::SPACE,
+        seeds = [b"observations", pool.key().as_ref()],
+        bump
+    )]
+    pub observations: Account<'info, Observations>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenEmergencyClaim<'info> {
+    /// CHECK: a pool or weighted pool; exits look the claim up by its seeds
+    #[account(owner = crate::ID @ AMMError::PoolNotFound)]
+    pub pool: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = 8 + EmergencyClaim::SPACE,
+        seeds = [b"emergency_claim", pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub emergency_claim: Account<'info, EmergencyClaim>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Observe<'info> {
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        seeds = [b"observations", pool.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Account<'info, Observations>,
+}
+
+#[derive(Accounts)]
+pub struct GetPoolInfo<'info> {
+    pub pool: Account<'info, Pool>,
+}
+
+#[derive(Accounts)]
+#[instruction(fee_rate: u64)]
+pub struct CreateClPool<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + ClPool::SPACE,
+        seeds = [
+            b"cl_pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            fee_rate.to_le_bytes().as_ref()
+        ],
+        bump,
+        // Mints sorted and distinct, as in CreatePool
+        constraint = token_a_mint.key() < token_b_mint.key() @ AMMError::InvalidTokenPair
+    )]
+    pub cl_pool: Box<Account<'info, ClPool>>,
+    
+    #[account(mut, seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// CHECK: Token A mint (Token-2022)
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint (Token-2022)
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// Pool-owned vault for token A
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"vault", cl_pool.key().as_ref(), token_a_mint.key().as_ref()],
+        bump,
+        token::mint = token_a_mint,
+        token::authority = cl_pool,
+        token::token_program = token_2022_program,
+    )]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Pool-owned vault for token B
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"vault", cl_pool.key().as_ref(), token_b_mint.key().as_ref()],
+        bump,
+        token::mint = token_b_mint,
+        token::authority = cl_pool,
+        token::token_program = token_2022_program,
+    )]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    pub cl_pool: Box<Account<'info, ClPool>>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TickArray::SPACE,
+        seeds = [b"tick_array", cl_pool.key().as_ref(), start_tick_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tick_array: Box<Account<'info, TickArray>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    pub cl_pool: Box<Account<'info, ClPool>>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Position::SPACE,
+        seeds = [
+            b"position",
+            cl_pool.key().as_ref(),
+            owner.key().as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyPosition<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"cl_pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            cl_pool.fee_rate.to_le_bytes().as_ref()
+        ],
+        bump = cl_pool.bump
+    )]
+    pub cl_pool: Box<Account<'info, ClPool>>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            cl_pool.key().as_ref(),
+            owner.key().as_ref(),
+            position.tick_lower.to_le_bytes().as_ref(),
+            position.tick_upper.to_le_bytes().as_ref()
+        ],
+        bump = position.bump
+    )]
+    pub position: Account<'info, Position>,
+    
+    /// Tick array holding the position's lower tick
+    #[account(mut, constraint = tick_array_lower.pool == cl_pool.key() @ AMMError::InvalidTickArray)]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+    /// Tick array holding the upper tick; omit when both ticks are in `tick_array_lower`
+    #[account(
+        mut,
+        constraint = tick_array_upper.pool == cl_pool.key() @ AMMError::InvalidTickArray,
+        constraint = tick_array_upper.key() != tick_array_lower.key() @ AMMError::InvalidTickArray
+    )]
+    pub tick_array_upper: Option<Box<Account<'info, TickArray>>>,
+    
+    /// CHECK: Token A mint
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// Owner's Token A account
+    #[account(
+        mut,
+        token::mint = token_a_mint,
+        token::authority = owner,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Owner's Token B account
+    #[account(
+        mut,
+        token::mint = token_b_mint,
+        token::authority = owner,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: Pool's Token A vault
+    #[account(mut, address = cl_pool.token_a_vault @ AMMError::InvalidVault)]
+    pub pool_token_a: UncheckedAccount<'info>,
+    /// CHECK: Pool's Token B vault
+    #[account(mut, address = cl_pool.token_b_vault @ AMMError::InvalidVault)]
+    pub pool_token_b: UncheckedAccount<'info>,
+    
+    pub owner: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> ModifyPosition<'info> {
+    /// The tick at `tick_index` from whichever supplied array holds it
+    fn tick_mut(&mut self, tick_index: i32) -> Result<&mut Tick> {
+        let tick_spacing = self.cl_pool.tick_spacing;
+        if let Some(offset) = self.tick_array_lower.tick_offset(tick_index, tick_spacing) {
+            return Ok(&mut self.tick_array_lower.ticks[offset]);
+        }
+        if let Some(tick_array_upper) = self.tick_array_upper.as_mut() {
+            if let Some(offset) = tick_array_upper.tick_offset(tick_index, tick_spacing) {
+                return Ok(&mut tick_array_upper.ticks[offset]);
+            }
+        }
+        Err(AMMError::InvalidTickArray.into())
+    }
+    
+    /// Apply a liquidity change to the position's lower and upper ticks
+    fn update_ticks(&mut self, liquidity_delta: i128) -> Result<()> {
+        let cl_pool = &self.cl_pool;
+        let (tick_current, growth_a, growth_b) = (
+            cl_pool.current_tick,
+            cl_pool.fee_growth_global_a_x64,
+            cl_pool.fee_growth_global_b_x64,
+        );
+        let (tick_lower, tick_upper) = (self.position.tick_lower, self.position.tick_upper);
+        self.tick_mut(tick_lower)?
+            .update(tick_lower, tick_current, liquidity_delta, false, growth_a, growth_b)?;
+        self.tick_mut(tick_upper)?
+            .update(tick_upper, tick_current, liquidity_delta, true, growth_a, growth_b)?;
+        Ok(())
+    }
+    
+    /// Credit fees earned since the last update and snapshot the growth inside the range
+    fn update_position_fees(&mut self) -> Result<()> {
+        let (tick_lower, tick_upper) = (self.position.tick_lower, self.position.tick_upper);
+        let lower = *self.tick_mut(tick_lower)?;
+        let upper = *self.tick_mut(tick_upper)?;
+        
+        let cl_pool = &self.cl_pool;
+        let inside_a = clmm_math::fee_growth_inside(
+            tick_lower,
+            tick_upper,
+            cl_pool.current_tick,
+            cl_pool.fee_growth_global_a_x64,
+            lower.fee_growth_outside_a_x64,
+            upper.fee_growth_outside_a_x64,
+        );
+        let inside_b = clmm_math::fee_growth_inside(
+            tick_lower,
+            tick_upper,
+            cl_pool.current_tick,
+            cl_pool.fee_growth_global_b_x64,
+            lower.fee_growth_outside_b_x64,
+            upper.fee_growth_outside_b_x64,
+        );
+        
+        let position = &mut self.position;
+        let earned_a = clmm_math::fees_earned(inside_a, position.fee_growth_inside_a_last_x64, position.liquidity)?;
+        let earned_b = clmm_math::fees_earned(inside_b, position.fee_growth_inside_b_last_x64, position.liquidity)?;
+        position.fees_owed_a = position.fees_owed_a.checked_add(earned_a)
+            .ok_or(AMMError::MathOverflow)?;
+        position.fees_owed_b = position.fees_owed_b.checked_add(earned_b)
+            .ok_or(AMMError::MathOverflow)?;
+        position.fee_growth_inside_a_last_x64 = inside_a;
+        position.fee_growth_inside_b_last_x64 = inside_b;
+        Ok(())
+    }
+    
+    /// Change the position's liquidity, and the pool's active liquidity when in range
+    fn apply_liquidity_delta(&mut self, liquidity_delta: i128) -> Result<()> {
+        let position = &mut self.position;
+        position.liquidity = clmm_math::add_liquidity_delta(position.liquidity, liquidity_delta)?;
+        
+        let cl_pool = &mut self.cl_pool;
+        if position.tick_lower <= cl_pool.current_tick && cl_pool.current_tick < position.tick_upper {
+            cl_pool.liquidity = clmm_math::add_liquidity_delta(cl_pool.liquidity, liquidity_delta)?;
+        }
+        Ok(())
+    }
+    
+    /// Pool -> owner transfers, PDA-signed and hook-aware; a skipped side is not
+    /// transferred and has no hook group
+    fn pay_out(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        amount_a: u64,
+        amount_b: u64,
+        skip_a: bool,
+        skip_b: bool,
+    ) -> Result<()> {
+        let token_a_key = self.token_a_mint.key();
+        let token_b_key = self.token_b_mint.key();
+        let fee_rate_bytes = self.cl_pool.fee_rate.to_le_bytes();
+        let pool_seeds = &[
+            b"cl_pool",
+            token_a_key.as_ref(),
+            token_b_key.as_ref(),
+            fee_rate_bytes.as_ref(),
+            &[self.cl_pool.bump],
+        ];
+        let pool_signer = &[&pool_seeds[..]];
+        
+        // Hook accounts in remaining_accounts: token A group, then token B group
+        let mut remaining = remaining_accounts;
+        
+        if !skip_a {
+            let hook_accounts_a = take_hook_accounts(&self.token_a_mint, &mut remaining)?;
+            transfer_checked_with_hooks(
+                self.pool_token_a.to_account_info(),
+                self.token_a_mint.to_account_info(),
+                self.user_token_a.to_account_info(),
+                self.cl_pool.to_account_info(),
+                hook_accounts_a,
+                amount_a,
+                get_mint_decimals(&self.token_a_mint)?,
+                pool_signer,
+            )?;
+        }
+        
+        if !skip_b {
+            let hook_accounts_b = take_hook_accounts(&self.token_b_mint, &mut remaining)?;
+            transfer_checked_with_hooks(
+                self.pool_token_b.to_account_info(),
+                self.token_b_mint.to_account_info(),
+                self.user_token_b.to_account_info(),
+                self.cl_pool.to_account_info(),
+                hook_accounts_b,
+                amount_b,
+                get_mint_decimals(&self.token_b_mint)?,
+                pool_signer,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ AMMError::Unauthorized,
+        constraint = position.liquidity == 0
+            && position.fees_owed_a == 0
+            && position.fees_owed_b == 0 @ AMMError::PositionNotEmpty
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SwapCl<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"cl_pool",
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            cl_pool.fee_rate.to_le_bytes().as_ref()
+        ],
+        bump = cl_pool.bump
+    )]
+    pub cl_pool: Box<Account<'info, ClPool>>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// CHECK: Token A mint
+    pub token_a_mint: UncheckedAccount<'info>,
+    /// CHECK: Token B mint
+    pub token_b_mint: UncheckedAccount<'info>,
+    
+    /// User's input token account (its mint selects the swap direction)
+    #[account(mut, token::token_program = token_2022_program)]
+    pub user_token_in: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// User's output token account (mint and owner checked against the direction)
+    #[account(mut, token::token_program = token_2022_program)]
+    pub user_token_out: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: Pool's Token A vault
+    #[account(mut, address = cl_pool.token_a_vault @ AMMError::InvalidVault)]
+    pub pool_token_a: UncheckedAccount<'info>,
+    /// CHECK: Pool's Token B vault
+    #[account(mut, address = cl_pool.token_b_vault @ AMMError::InvalidVault)]
+    pub pool_token_b: UncheckedAccount<'info>,
+    
+    /// Tick array holding the current tick
+    #[account(mut, constraint = tick_array_0.pool == cl_pool.key() @ AMMError::InvalidTickArray)]
+    pub tick_array_0: Box<Account<'info, TickArray>>,
+    /// Next tick array in swap direction, if the swap may reach it
+    #[account(mut, constraint = tick_array_1.pool == cl_pool.key() @ AMMError::InvalidTickArray)]
+    pub tick_array_1: Option<Box<Account<'info, TickArray>>>,
+    /// Tick array after `tick_array_1`, if the swap may reach it
+    #[account(mut, constraint = tick_array_2.pool == cl_pool.key() @ AMMError::InvalidTickArray)]
+    pub tick_array_2: Option<Box<Account<'info, TickArray>>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> SwapCl<'info> {
+    /// Check pause state, resolve the direction from the input account, validate hooks and user accounts
+    fn prepare(&mut self, amount_in: u64) -> Result<bool> {
+        self.amm_config.require_not_paused()?;
+        self.cl_pool.require_not_paused()?;
+        
+        let input_mint = self.user_token_in.mint;
+        let a_to_b = if input_mint == self.cl_pool.token_a_mint {
+            true
+        } else if input_mint == self.cl_pool.token_b_mint {
+            false
+        } else {
+            return Err(AMMError::InvalidTokenPair.into());
+        };
+        
+        msg!("🔄 Concentrated swap: {} input, direction: {}",
+             amount_in, if a_to_b { "A→B" } else { "B→A" });
+        
+        // Validate the mints' current hooks (a mint authority can change its hook program)
+        let hook_registry = self.hook_registry.as_ref();
+        let hook_a = validate_transfer_hooks_real(&self.token_a_mint, &self.amm_config, hook_registry)?;
+        let hook_b = validate_transfer_hooks_real(&self.token_b_mint, &self.amm_config, hook_registry)?;
+        self.cl_pool.refresh_hook_programs(hook_a, hook_b);
+        
+        // User accounts must match the swap direction and belong to the user
+        let mint_out = if a_to_b { self.cl_pool.token_b_mint } else { self.cl_pool.token_a_mint };
+        require_keys_eq!(self.user_token_out.mint, mint_out, AMMError::InvalidTokenAccount);
+        require_keys_eq!(self.user_token_in.owner, self.user.key(), AMMError::InvalidTokenAccount);
+        require_keys_eq!(self.user_token_out.owner, self.user.key(), AMMError::InvalidTokenAccount);
+        
+        Ok(a_to_b)
+    }
+    
+    /// (input mint, output mint) for the direction
+    fn mints(&self, a_to_b: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        if a_to_b {
+            (self.token_a_mint.to_account_info(), self.token_b_mint.to_account_info())
+        } else {
+            (self.token_b_mint.to_account_info(), self.token_a_mint.to_account_info())
+        }
+    }
+    
+    /// Run the swap against the pool's ticks and write back price, tick, liquidity and fee growth
+    fn execute(
+        &mut self,
+        a_to_b: bool,
+        amount: u64,
+        sqrt_price_limit_x64: u128,
+    ) -> Result<ClSwapResult> {
+        let cl_pool = &mut self.cl_pool;
+        let tick_spacing = cl_pool.tick_spacing;
+        
+        let sqrt_price_limit_x64 = match sqrt_price_limit_x64 {
+            0 if a_to_b => clmm_math::MIN_SQRT_PRICE_X64 + 1,
+            0 => clmm_math::MAX_SQRT_PRICE_X64 - 1,
+            limit => limit,
+        };
+        let limit_valid = if a_to_b {
+            sqrt_price_limit_x64 < cl_pool.sqrt_price_x64
+                && sqrt_price_limit_x64 > clmm_math::MIN_SQRT_PRICE_X64
+        } else {
+            sqrt_price_limit_x64 > cl_pool.sqrt_price_x64
+                && sqrt_price_limit_x64 < clmm_math::MAX_SQRT_PRICE_X64
+        };
+        require!(limit_valid, AMMError::InvalidSqrtPrice);
+        
+        // Tick arrays must be contiguous in swap direction from the current tick's array,
+        // so no initialized tick can be skipped
+        let mut tick_arrays: Vec<&mut TickArray> = vec![&mut self.tick_array_0];
+        if let Some(tick_array) = self.tick_array_1.as_mut() {
+            tick_arrays.push(tick_array);
+        }
+        if let Some(tick_array) = self.tick_array_2.as_mut() {
+            require!(tick_arrays.len() == 2, AMMError::InvalidTickArray);
+            tick_arrays.push(tick_array);
+        }
+        let span = TickArray::ticks_covered(tick_spacing);
+        let first_start = TickArray::start_index_for(cl_pool.current_tick, tick_spacing);
+        for (i, tick_array) in tick_arrays.iter().enumerate() {
+            let offset = span * i as i32;
+            let expected = if a_to_b { first_start - offset } else { first_start + offset };
+            require!(tick_array.start_tick_index == expected, AMMError::InvalidTickArray);
+        }
+        let last_start = tick_arrays[tick_arrays.len() - 1].start_tick_index;
+        
+        let mut amount_remaining = amount;
+        let mut amount_out = 0u64;
+        let mut fee_total = 0u64;
+        let mut sqrt_price = cl_pool.sqrt_price_x64;
+        let mut tick_current = cl_pool.current_tick;
+        let mut liquidity = cl_pool.liquidity;
+        let mut fee_growth_global_in = if a_to_b {
+            cl_pool.fee_growth_global_a_x64
+        } else {
+            cl_pool.fee_growth_global_b_x64
+        };
+        
+        while amount_remaining > 0 && sqrt_price != sqrt_price_limit_x64 {
+            let covered = if a_to_b { tick_current >= last_start } else { tick_current < last_start + span };
+            require!(covered, AMMError::TickArrayMissing);
+            
+            // Next initialized tick in the supplied arrays, else the edge of the last array
+            let next_initialized = tick_arrays
+                .iter()
+                .filter_map(|tick_array| tick_array.next_initialized_tick(tick_current, tick_spacing, a_to_b))
+                .reduce(|a, b| if a_to_b { a.max(b) } else { a.min(b) });
+            let (tick_next, initialized) = match next_initialized {
+                Some(tick) => (tick, true),
+                None if a_to_b => (last_start.max(clmm_math::MIN_TICK), false),
+                None => ((last_start + span).min(clmm_math::MAX_TICK), false),
+            };
+            
+            let sqrt_price_next_tick = clmm_math::sqrt_price_from_tick(tick_next)?;
+            let sqrt_price_target = if a_to_b {
+                sqrt_price_next_tick.max(sqrt_price_limit_x64)
+            } else {
+                sqrt_price_next_tick.min(sqrt_price_limit_x64)
+            };
+            
+            let step = clmm_math::compute_swap_step(
+                sqrt_price,
+                sqrt_price_target,
+                liquidity,
+                amount_remaining,
+                cl_pool.fee_rate,
+            )?;
+            amount_remaining = amount_remaining
+                .checked_sub(step.amount_in + step.fee_amount)
+                .ok_or(AMMError::MathOverflow)?;
+            amount_out = amount_out.checked_add(step.amount_out).ok_or(AMMError::MathOverflow)?;
+            fee_total = fee_total.checked_add(step.fee_amount).ok_or(AMMError::MathOverflow)?;
+            fee_growth_global_in = fee_growth_global_in
+                .wrapping_add(clmm_math::fee_growth_delta(step.fee_amount, liquidity)?);
+            
+            let sqrt_price_start = sqrt_price;
+            sqrt_price = step.sqrt_price_next_x64;
+            if sqrt_price == sqrt_price_next_tick {
+                if initialized {
+                    let (growth_a, growth_b) = if a_to_b {
+                        (fee_growth_global_in, cl_pool.fee_growth_global_b_x64)
+                    } else {
+                        (cl_pool.fee_growth_global_a_x64, fee_growth_global_in)
+                    };
+                    let tick = tick_arrays
+                        .iter_mut()
+                        .find_map(|tick_array| tick_array.tick_mut(tick_next, tick_spacing))
+                        .ok_or(AMMError::InvalidTickArray)?;
+                    let liquidity_delta = tick.cross(growth_a, growth_b, a_to_b);
+                    liquidity = clmm_math::add_liquidity_delta(liquidity, liquidity_delta)?;
+                }
+                tick_current = if a_to_b { tick_next - 1 } else { tick_next };
+            } else if sqrt_price != sqrt_price_start {
+                tick_current = clmm_math::tick_from_sqrt_price(sqrt_price)?;
+            }
+        }
+        
+        cl_pool.sqrt_price_x64 = sqrt_price;
+        cl_pool.current_tick = tick_current;
+        cl_pool.liquidity = liquidity;
+        if a_to_b {
+            cl_pool.fee_growth_global_a_x64 = fee_growth_global_in;
+        } else {
+            cl_pool.fee_growth_global_b_x64 = fee_growth_global_in;
+        }
+        
+        Ok(ClSwapResult {
+            amount_in: amount - amount_remaining,
+            amount_out,
+            fee_amount: fee_total,
+        })
+    }
+    
+    /// Move tokens with hook-aware transfers
+    fn settle(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        a_to_b: bool,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<()> {
+        let (mint_in_info, mint_out_info) = self.mints(a_to_b);
+        let (vault_in, vault_out) = if a_to_b {
+            (self.pool_token_a.to_account_info(), self.pool_token_b.to_account_info())
+        } else {
+            (self.pool_token_b.to_account_info(), self.pool_token_a.to_account_info())
+        };
+        let decimals_in = get_mint_decimals(&mint_in_info)?;
+        let decimals_out = get_mint_decimals(&mint_out_info)?;
+        
+        // Hook accounts in remaining_accounts: input mint group, then output mint group
+        let mut remaining = remaining_accounts;
+        let hook_accounts_in = take_hook_accounts(&mint_in_info, &mut remaining)?;
+        let hook_accounts_out = take_hook_accounts(&mint_out_info, &mut remaining)?;
+        
+        transfer_checked_with_hooks(
+            self.user_token_in.to_account_info(),
+            mint_in_info,
+            vault_in,
+            self.user.to_account_info(),
+            hook_accounts_in,
+            amount_in,
+            decimals_in,
+            &[],
+        )?;
+        
+        let token_a_key = self.token_a_mint.key();
+        let token_b_key = self.token_b_mint.key();
+        let fee_rate_bytes = self.cl_pool.fee_rate.to_le_bytes();
+        let pool_seeds = &[
+            b"cl_pool",
+            token_a_key.as_ref(),
+            token_b_key.as_ref(),
+            fee_rate_bytes.as_ref(),
+            &[self.cl_pool.bump],
+        ];
+        let pool_signer = &[&pool_seeds[..]];
+        
+        transfer_checked_with_hooks(
+            vault_out,
+            mint_out_info,
+            self.user_token_out.to_account_info(),
+            self.cl_pool.to_account_info(),
+            hook_accounts_out,
+            amount_out,
+            decimals_out,
+            pool_signer,
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: [u8; 32])]
+pub struct CreateWeightedPool<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + WeightedPool::SPACE,
+        seeds = [b"weighted_pool", pool_id.as_ref()],
+        bump
+    )]
+    pub weighted_pool: Box<Account<'info, WeightedPool>>,
+    
+    #[account(mut, seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"lp_mint", weighted_pool.key().as_ref()],
+        bump,
+        mint::decimals = LP_DECIMALS,
+        mint::authority = weighted_pool,
+        mint::token_program = token_2022_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct WeightedJoinExit<'info> {
+    #[account(
+        mut,
+        seeds = [b"weighted_pool", weighted_pool.pool_id.as_ref()],
+        bump = weighted_pool.bump
+    )]
+    pub weighted_pool: Box<Account<'info, WeightedPool>>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    #[account(
+        mut,
+        address = weighted_pool.lp_mint,
+        mint::token_program = token_2022_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// Receives minted LP tokens on joins, burned from on exits
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Records what an emergency exit skips; required to skip an asset or claim
+    #[account(
+        mut,
+        seeds = [b"emergency_claim", weighted_pool.key().as_ref(), user.key().as_ref()],
+        bump = emergency_claim.bump
+    )]
+    pub emergency_claim: Option<Account<'info, EmergencyClaim>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> WeightedJoinExit<'info> {
+    /// Split `[mint, vault, user_token]` per asset off `remaining_accounts` and check them
+    /// against the pool; the rest are hook groups
+    fn split_asset_accounts(
+        &self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<(&'info [AccountInfo<'info>], &'info [AccountInfo<'info>])> {
+        let asset_len = self.weighted_pool.assets.len() * WEIGHTED_ASSET_ACCOUNTS;
+        require!(remaining_accounts.len() >= asset_len, AMMError::InvalidAssetCount);
+        let (asset_accounts, hook_accounts) = remaining_accounts.split_at(asset_len);
+        
+        for (asset, accounts) in self.weighted_pool.assets.iter().zip(asset_accounts.chunks(WEIGHTED_ASSET_ACCOUNTS)) {
+            let [mint_info, vault_info, user_token_info] = accounts else {
+                return Err(AMMError::InvalidAssetCount.into());
+            };
+            require_keys_eq!(mint_info.key(), asset.mint, AMMError::InvalidTokenPair);
+            require_keys_eq!(vault_info.key(), asset.vault, AMMError::InvalidVault);
+            let user_token = InterfaceAccount::<TokenAccount>::try_from(user_token_info)?;
+            require_keys_eq!(user_token.mint, asset.mint, AMMError::InvalidTokenAccount);
+            require_keys_eq!(user_token.owner, self.user.key(), AMMError::InvalidTokenAccount);
+        }
+        Ok((asset_accounts, hook_accounts))
+    }
+    
+    /// Burn `lp_amount` for its pro-rata share of every asset, paying out those not
+    /// skipped and recording the rest in `emergency_claim`. Returns the amounts sent
+    /// before transfer fees.
+    fn exit(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        lp_amount: u64,
+        min_amounts_out: &[u64],
+        skip_assets: &[bool],
+    ) -> Result<Vec<u64>> {
+        let pool = &self.weighted_pool;
+        require!(min_amounts_out.len() == pool.assets.len(), AMMError::InvalidAssetCount);
+        require!(lp_amount > 0, AMMError::InsufficientLPTokens);
+        require!(
+            self.user_lp_token.amount >= lp_amount,
+            AMMError::InsufficientLPTokens
+        );
+        let (asset_accounts, remaining) = self.split_asset_accounts(remaining_accounts)?;
+        
+        // No registry check here: LPs can always withdraw, even if a hook was revoked
+        
+        let mut shares = Vec::with_capacity(pool.assets.len());
+        let mut amounts_out = Vec::with_capacity(pool.assets.len());
+        for (((asset, accounts), min_amount_out), skip) in pool.assets.iter().zip(asset_accounts.chunks(WEIGHTED_ASSET_ACCOUNTS)).zip(min_amounts_out).zip(skip_assets) {
+            let share = math::withdraw_amount(lp_amount, asset.balance, pool.lp_token_supply)?;
+            let amount = if *skip { 0 } else { share };
+            let net_amount = if asset.has_transfer_fee() {
+                amount.checked_sub(get_transfer_fee(&accounts[0], amount)?)
+                    .ok_or(AMMError::MathOverflow)?
+            } else {
+                amount
+            };
+            require!(net_amount >= *min_amount_out, AMMError::SlippageExceeded);
+            shares.push(share);
+            amounts_out.push(amount);
+        }
+        
+        // Skipped shares leave the pool and are owed to the provider
+        let owed: Vec<u64> = shares.iter().zip(&amounts_out).map(|(share, amount)| share - amount).collect();
+        if owed.iter().any(|amount| *amount > 0) {
+            self.emergency_claim.as_mut()
+                .ok_or(AMMError::EmergencyClaimRequired)?
+                .add(&owed)?;
+        }
+        
+        self.pay_out(asset_accounts, remaining, &amounts_out, skip_assets)?;
+        
+        self.burn_lp(lp_amount)?;
+        
+        let pool = &mut self.weighted_pool;
+        for (asset, share) in pool.assets.iter_mut().zip(&shares) {
+            asset.balance = asset.balance.checked_sub(*share).ok_or(AMMError::MathOverflow)?;
+        }
+        pool.lp_token_supply = pool.lp_token_supply.checked_sub(lp_amount)
+            .ok_or(AMMError::MathOverflow)?;
+        
+        msg!("✅ Weighted exit: {:?} for {} LP tokens", amounts_out, lp_amount);
+        Ok(amounts_out)
+    }
+    
+    /// Pool -> user transfers for each asset not skipped; hook groups follow the
+    /// asset accounts, one per asset transferred
+    fn pay_out(
+        &self,
+        asset_accounts: &'info [AccountInfo<'info>],
+        mut remaining: &'info [AccountInfo<'info>],
+        amounts: &[u64],
+        skip_assets: &[bool],
+    ) -> Result<()> {
+        let pool = &self.weighted_pool;
+        let pool_seeds = &[b"weighted_pool", pool.pool_id.as_ref(), &[pool.bump]];
+        let pool_signer = &[&pool_seeds[..]];
+        
+        for ((accounts, amount), skip) in asset_accounts.chunks(WEIGHTED_ASSET_ACCOUNTS).zip(amounts).zip(skip_assets) {
+            let [mint_info, vault_info, user_token_info] = accounts else {
+                return Err(AMMError::InvalidAssetCount.into());
+            };
+            if *skip {
+                continue;
+            }
+            let hook_accounts = take_hook_accounts(mint_info, &mut remaining)?;
+            transfer_checked_with_hooks(
+                vault_info.clone(),
+                mint_info.clone(),
+                user_token_info.clone(),
+                self.weighted_pool.to_account_info(),
+                hook_accounts,
+                *amount,
+                get_mint_decimals(mint_info)?,
+                pool_signer,
+            )?;
+        }
+        Ok(())
+    }
+    
+    fn mint_lp(&self, amount: u64) -> Result<()> {
+        let pool = &self.weighted_pool;
+        let pool_seeds = &[b"weighted_pool", pool.pool_id.as_ref(), &[pool.bump]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(),
+                MintTo {
+                    mint: self.lp_mint.to_account_info(),
+                    to: self.user_lp_token.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            amount,
+        )
+    }
+    
+    fn burn_lp(&self, amount: u64) -> Result<()> {
+        token_interface::burn(
+            CpiContext::new(
+                self.token_2022_program.to_account_info(),
+                Burn {
+                    mint: self.lp_mint.to_account_info(),
+                    from: self.user_lp_token.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct WeightedSingle<'info> {
+    #[account(
+        mut,
+        seeds = [b"weighted_pool", weighted_pool.pool_id.as_ref()],
+        bump = weighted_pool.bump
+    )]
+    pub weighted_pool: Box<Account<'info, WeightedPool>>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// CHECK: Mint of the asset joined with or exited into
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: Pool's vault for `mint`, checked against the pool
+    #[account(mut)]
+    pub pool_vault: UncheckedAccount<'info>,
+    
+    /// User's account for `mint`
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        address = weighted_pool.lp_mint,
+        mint::token_program = token_2022_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// Receives minted LP tokens on joins, burned from on exits
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> WeightedSingle<'info> {
+    /// Index of `mint` in the pool, after checking the vault
+    fn asset_index(&self) -> Result<usize> {
+        let index = self.weighted_pool.asset_index(&self.mint.key())?;
+        require_keys_eq!(self.pool_vault.key(), self.weighted_pool.assets[index].vault, AMMError::InvalidVault);
+        Ok(index)
+    }
+    
+    fn mint_lp(&self, amount: u64) -> Result<()> {
+        let pool = &self.weighted_pool;
+        let pool_seeds = &[b"weighted_pool", pool.pool_id.as_ref(), &[pool.bump]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(),
+                MintTo {
+                    mint: self.lp_mint.to_account_info(),
+                    to: self.user_lp_token.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            amount,
+        )
+    }
+    
+    fn burn_lp(&self, amount: u64) -> Result<()> {
+        token_interface::burn(
+            CpiContext::new(
+                self.token_2022_program.to_account_info(),
+                Burn {
+                    mint: self.lp_mint.to_account_info(),
+                    from: self.user_lp_token.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct SwapWeighted<'info> {
+    #[account(
+        mut,
+        seeds = [b"weighted_pool", weighted_pool.pool_id.as_ref()],
+        bump = weighted_pool.bump
+    )]
+    pub weighted_pool: Box<Account<'info, WeightedPool>>,
+    
+    #[account(seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AMMConfig>,
+    
+    /// Registry of approved hook programs, required when a mint has a transfer hook
+    #[account(constraint = amm_config.hook_registry == Some(hook_registry.key()) @ AMMError::HookValidationFailed)]
+    pub hook_registry: Option<Account<'info, hook_registry::HookRegistry>>,
+    
+    /// CHECK: Input mint, checked against the pool
+    pub mint_in: UncheckedAccount<'info>,
+    /// CHECK: Output mint, checked against the pool
+    pub mint_out: UncheckedAccount<'info>,
+    
+    /// CHECK: Pool's vault for the input mint
+    #[account(mut)]
+    pub pool_vault_in: UncheckedAccount<'info>,
+    /// CHECK: Pool's vault for the output mint
+    #[account(mut)]
+    pub pool_vault_out: UncheckedAccount<'info>,
+    
+    /// User's input token account
+    #[account(
+        mut,
+        token::mint = mint_in,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_in: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// User's output token account
+    #[account(
+        mut,
+        token::mint = mint_out,
+        token::authority = user,
+        token::token_program = token_2022_program,
+    )]
+    pub user_token_out: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub user: Signer<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+// Data structures
+#[account]
+pub struct AMMConfig {
+    pub authority: Pubkey,                // 32 bytes - owner
+    pub fee_rate: u64,                    // 8 bytes - basis points, default tier
+    pub total_pools: u32,                 // 4 bytes
+    pub created_at: i64,                  // 8 bytes
+    pub hook_registry: Option<Pubkey>,    // 1 + 32 = 33 bytes
+    pub fee_tiers: Vec<u64>,              // 4 + (8 * 8) = 68 bytes - basis points
+    pub protocol_fee_rate: u64,           // 8 bytes - basis points of the swap fee
+    pub treasury: Pubkey,                 // 32 bytes - owner of the fee-collecting token accounts
+    pub pending_authority: Option<Pubkey>, // 1 + 32 = 33 bytes - proposed owner awaiting acceptance
+    pub fee_manager: Pubkey,              // 32 bytes - fee tiers, protocol fee, fee collection
+    pub pauser: Pubkey,                   // 32 bytes
+    pub registry_manager: Pubkey,         // 32 bytes - hook registry
+    pub paused: bool,                     // 1 byte - blocks swaps and deposits in every pool
+    pub paused_hook_programs: Vec<Pubkey>, // 4 + (32 * 8) = 260 bytes - pools using these hooks can't trade
+}
+
+impl AMMConfig {
+    pub const SPACE: usize = 32 + 8 + 4 + 8 + 33 + 4 + (8 * MAX_FEE_TIERS) + 8 + 32 + 33 + 32 + 32 + 32
+        + 1 + 4 + (32 * MAX_PAUSED_HOOK_PROGRAMS); // 583 bytes
+    
+    /// Swaps and deposits are refused while the AMM is paused
+    pub fn require_not_paused(&self) -> Result<()> {
+        require!(!self.paused, AMMError::AMMPaused);
+        Ok(())
+    }
+}
+
+/// Maximum number of fee tiers in AMMConfig
+pub const MAX_FEE_TIERS: usize = 8;
+
+/// Maximum number of hook programs paused at once
+pub const MAX_PAUSED_HOOK_PROGRAMS: usize = 8;
+
+#[account]
+pub struct Pool {
+    pub token_a_mint: Pubkey,            // 32 bytes
+    pub token_b_mint: Pubkey,            // 32 bytes
+    pub creator: Pubkey,                 // 32 bytes
+    pub created_at: i64,                 // 8 bytes
+    pub fee_rate: u64,                   // 8 bytes
+    pub current_price: u64,              // 8 bytes
+    pub total_liquidity_a: u64,          // 8 bytes
+    pub total_liquidity_b: u64,          // 8 bytes
+    pub lp_token_supply: u64,            // 8 bytes - includes locked MINIMUM_LIQUIDITY
+    pub hook_enabled: bool,              // 1 byte
+    pub token_program_id: Pubkey,        // 32 bytes
+    pub bump: u8,                        // 1 byte
+    pub lp_mint: Pubkey,                 // 32 bytes
+    pub token_a_vault: Pubkey,           // 32 bytes
+    pub token_b_vault: Pubkey,           // 32 bytes
+    pub token_a_hook_program: Option<Pubkey>, // 1 + 32 = 33 bytes
+    pub token_b_hook_program: Option<Pubkey>, // 1 + 32 = 33 bytes
+    pub token_a_flags: u8,               // 1 byte - MINT_FLAG_* bits
+    pub token_b_flags: u8,               // 1 byte - MINT_FLAG_* bits
+    pub protocol_fees_a: u64,            // 8 bytes - accrued, not part of the reserves
+    pub protocol_fees_b: u64,            // 8 bytes - accrued, not part of the reserves
+    pub price_a_cumulative: u128,        // 16 bytes - sum of (B per A * PRICE_SCALE) * seconds
+    pub price_b_cumulative: u128,        // 16 bytes - sum of (A per B * PRICE_SCALE) * seconds
+    pub last_update_timestamp: i64,      // 8 bytes
+    pub pool_type: PoolType,             // 1 byte
+    pub amp_initial: u64,                // 8 bytes - StableSwap amplification at ramp start
+    pub amp_target: u64,                 // 8 bytes - StableSwap amplification at ramp end
+    pub amp_ramp_start: i64,             // 8 bytes
+    pub amp_ramp_end: i64,               // 8 bytes
+    pub paused: bool,                    // 1 byte - blocks swaps and deposits
+    pub emergency_mode: bool,            // 1 byte - also blocks them, and enables emergency_withdraw
+}
+
+impl Pool {
+    pub const SPACE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 32 + 1 + 32 + 32 + 32 + 33 + 33 + 1 + 1 + 8 + 8 + 16 + 16 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 1; // 433 bytes
+    
+    /// Swaps and deposits are refused while the pool is paused or in emergency mode
+    pub fn require_not_paused(&self) -> Result<()> {
+        require!(!self.paused && !self.emergency_mode, AMMError::PoolPaused);
+        Ok(())
+    }
+    
+    pub fn token_a_has_transfer_fee(&self) -> bool {
+        self.token_a_flags & MINT_FLAG_TRANSFER_FEE != 0
+    }
+    
+    pub fn token_b_has_transfer_fee(&self) -> bool {
+        self.token_b_flags & MINT_FLAG_TRANSFER_FEE != 0
+    }
+    
+    /// Cumulative prices extrapolated to `now` from the current reserves
+    pub fn cumulative_prices_at(&self, now: i64) -> Result<(u128, u128)> {
+        let elapsed = now.saturating_sub(self.last_update_timestamp);
+        if elapsed <= 0 || self.total_liquidity_a == 0 || self.total_liquidity_b == 0 {
+            return Ok((self.price_a_cumulative, self.price_b_cumulative));
+        }
+        
+        let price_a = self.spot_price(true, now)?;
+        let price_b = self.spot_price(false, now)?;
+        Ok((
+            self.price_a_cumulative.wrapping_add(price_a.wrapping_mul(elapsed as u128)),
+            self.price_b_cumulative.wrapping_add(price_b.wrapping_mul(elapsed as u128)),
+        ))
+    }
+    
+    /// Fold the time since the last update into the accumulators; call before reserves change
+    pub fn accumulate_prices(&mut self, now: i64) -> Result<()> {
+        let (price_a_cumulative, price_b_cumulative) = self.cumulative_prices_at(now)?;
+        self.price_a_cumulative = price_a_cumulative;
+        self.price_b_cumulative = price_b_cumulative;
+        self.last_update_timestamp = self.last_update_timestamp.max(now);
+        Ok(())
+    }
+    
+    /// StableSwap amplification at `now`, following any active ramp
+    pub fn current_amp(&self, now: i64) -> u64 {
+        stable_math::current_amp(
+            self.amp_initial,
+            self.amp_target,
+            self.amp_ramp_start,
+            self.amp_ramp_end,
+            now,
+        )
+    }
+    
+    /// (reserve in, reserve out) for the direction
+    pub fn reserves(&self, a_to_b: bool) -> (u64, u64) {
+        if a_to_b {
+            (self.total_liquidity_a, self.total_liquidity_b)
+        } else {
+            (self.total_liquidity_b, self.total_liquidity_a)
+        }
+    }
+    
+    /// Whether the (input, output) mints charge transfer fees for the direction
+    pub fn transfer_fee_sides(&self, a_to_b: bool) -> (bool, bool) {
+        if a_to_b {
+            (self.token_a_has_transfer_fee(), self.token_b_has_transfer_fee())
+        } else {
+            (self.token_b_has_transfer_fee(), self.token_a_has_transfer_fee())
+        }
+    }
+    
+    /// Move reserves for a swap and accrue the protocol share of the fee on the input side
+    pub fn apply_swap(
+        &mut self,
+        a_to_b: bool,
+        amount_received: u64,
+        amount_out: u64,
+        protocol_fee: u64,
+    ) -> Result<()> {
+        let (reserve_in, reserve_out, protocol_fees_in) = if a_to_b {
+            (self.total_liquidity_a, self.total_liquidity_b, self.protocol_fees_a)
+        } else {
+            (self.total_liquidity_b, self.total_liquidity_a, self.protocol_fees_b)
+        };
+        
+        let new_reserve_in = reserve_in
+            .checked_add(amount_received)
+            .and_then(|v| v.checked_sub(protocol_fee))
+            .ok_or(AMMError::MathOverflow)?;
+        let new_reserve_out = reserve_out.checked_sub(amount_out).ok_or(AMMError::MathOverflow)?;
+        let new_protocol_fees_in = protocol_fees_in.checked_add(protocol_fee)
+            .ok_or(AMMError::MathOverflow)?;
+        
+        if a_to_b {
+            self.total_liquidity_a = new_reserve_in;
+            self.total_liquidity_b = new_reserve_out;
+            self.protocol_fees_a = new_protocol_fees_in;
+        } else {
+            self.total_liquidity_b = new_reserve_in;
+            self.total_liquidity_a = new_reserve_out;
+            self.protocol_fees_b = new_protocol_fees_in;
+        }
+        self.refresh_current_price();
+        Ok(())
+    }
+    
+    /// Spot price of the input token in output tokens for the direction, scaled by
+    /// PRICE_SCALE, on the pool's curve at `now`
+    pub fn spot_price(&self, a_to_b: bool, now: i64) -> Result<u128> {
+        let (reserve_in, reserve_out) = self.reserves(a_to_b);
+        let price = match self.pool_type {
+            PoolType::ConstantProduct => math::spot_price(reserve_in, reserve_out)?,
+            PoolType::StableSwap => stable_math::spot_price(reserve_in, reserve_out, self.current_amp(now))?,
+        };
+        Ok(price)
+    }
+    
+    /// Set `current_price` (B per A, scaled by PRICE_SCALE) from the reserves. Callers
+    /// accumulate prices first, so `last_update_timestamp` is the current time.
+    pub fn refresh_current_price(&mut self) {
+        if let Ok(price) = self.spot_price(true, self.last_update_timestamp) {
+            self.current_price = u64::try_from(price).unwrap_or(u64::MAX);
+        }
+    }
+    
+    /// Track hook program changes made by the mint authorities since pool creation
+    pub fn refresh_hook_programs(&mut self, hook_a: Option<Pubkey>, hook_b: Option<Pubkey>) {
+        if self.token_a_hook_program != hook_a || self.token_b_hook_program != hook_b {
+            msg!("🔄 Hook programs changed: A={:?}, B={:?}", hook_a, hook_b);
+            self.token_a_hook_program = hook_a;
+            self.token_b_hook_program = hook_b;
+            self.hook_enabled = hook_a.is_some() || hook_b.is_some();
+        }
+    }
+}
+
+/// What emergency exits left owed to an LP, one entry per pool asset (A, B for a pool)
+#[account]
+pub struct EmergencyClaim {
+    pub pool: Pubkey,                    // 32 bytes - pool or weighted pool
+    pub owner: Pubkey,                   // 32 bytes
+    pub amounts: Vec<u64>,               // 4 + (8 * 8) = 68 bytes
+    pub bump: u8,                        // 1 byte
+}
+
+impl EmergencyClaim {
+    pub const SPACE: usize = 32 + 32 + 4 + (8 * MAX_WEIGHTED_ASSETS) + 1; // 133 bytes
+    
+    /// Add `amounts` (one per pool asset) to what is owed
+    pub fn add(&mut self, amounts: &[u64]) -> Result<()> {
+        if self.amounts.len() < amounts.len() {
+            self.amounts.resize(amounts.len(), 0);
+        }
+        for (owed, amount) in self.amounts.iter_mut().zip(amounts) {
+            *owed = owed.checked_add(*amount).ok_or(AMMError::MathOverflow)?;
+        }
+        Ok(())
+    }
+    
+    /// Clear and return what is owed for each asset not skipped
+    pub fn take(&mut self, skip_assets: &[bool]) -> Vec<u64> {
+        skip_assets
+            .iter()
+            .enumerate()
+            .map(|(index, skip)| match self.amounts.get_mut(index) {
+                Some(owed) if !skip => std::mem::take(owed),
+                _ => 0,
+            })
+            .collect()
+    }
+}
+
+/// Ring buffer of a pool's cumulative price readings
+#[account]
+pub struct Observations {
+    pub pool: Pubkey,                    // 32 bytes
+    pub index: u16,                      // 2 bytes - slot of the newest observation
+    pub observations: Vec<Observation>,  // 4 + (64 * 40) = 2564 bytes
+    pub bump: u8,                        // 1 byte
+}
+
+impl Observations {
+    pub const SPACE: usize = 32 + 2 + 4 + (Observation::SPACE * OBSERVATION_CAPACITY) + 1; // 2599 bytes
+    
+    /// Store the pool's current accumulators, at most once per timestamp
+    pub fn record(&mut self, pool: &Pool) {
+        if let Some(latest) = self.observations.get(self.index as usize) {
+            if latest.timestamp == pool.last_update_timestamp {
+                return;
+            }
+        }
+        
+        let observation = Observation {
+            timestamp: pool.last_update_timestamp,
+            price_a_cumulative: pool.price_a_cumulative,
+            price_b_cumulative: pool.price_b_cumulative,
+        };
+        
+        if self.observations.is_empty() {
+            self.observations.push(observation);
+            self.index = 0;
+            return;
+        }
+        
+        let next = (self.index as usize + 1) % OBSERVATION_CAPACITY;
+        if next == self.observations.len() {
+            self.observations.push(observation);
+        } else {
+            self.observations[next] = observation;
+        }
+        self.index = next as u16;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct Observation {
+    pub timestamp: i64,                  // 8 bytes
+    pub price_a_cumulative: u128,        // 16 bytes
+    pub price_b_cumulative: u128,        // 16 bytes
+}
+
+impl Observation {
+    pub const SPACE: usize = 8 + 16 + 16; // 40 bytes
+}
+
+/// Returned by `observe`; prices are scaled by `math::PRICE_SCALE`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TwapResult {
+    pub price_a: u128,                   // B per A
+    pub price_b: u128,                   // A per B
+    pub window_seconds: i64,             // actual window covered
+}
+
+/// Number of observations kept per pool
+pub const OBSERVATION_CAPACITY: usize = 64;
+
+/// Invariant a pool trades on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolType {
+    ConstantProduct,
+    StableSwap,
+}
+
+/// AMM admin roles the owner can hand out
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdminRole {
+    FeeManager,
+    Pauser,
+    RegistryManager,
+}
+
+/// Whether a swap amount fixes the input or the output
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapMode {
+    ExactIn,
+    ExactOut,
+}
+
+/// Returned by `quote_swap`; also drives `swap`, `swap_exact_out` and `swap_route`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapQuoteResult {
+    pub amount_in: u64,                  // paid by the user
+    pub amount_out: u64,                 // received by the user, after transfer fees
+    pub amount_received: u64,            // reaching the input vault
+    pub pool_amount_out: u64,            // leaving the output vault
+    pub fee_amount: u64,                 // swap fee, including the protocol share
+    pub protocol_fee: u64,
+    pub transfer_fee_in: u64,
+    pub transfer_fee_out: u64,
+    pub new_reserve_in: u64,
+    pub new_reserve_out: u64,
+    pub price_impact_bps: u64,           // execution vs spot price, excluding fees
+}
+
+/// Concentrated-liquidity pool: liquidity is provided over tick ranges by positions.
+///
+/// Swap fees go entirely to the positions in range; there is no protocol share.
+#[account]
+pub struct ClPool {
+    pub token_a_mint: Pubkey,            // 32 bytes
+    pub token_b_mint: Pubkey,            // 32 bytes
+    pub creator: Pubkey,                 // 32 bytes
+    pub created_at: i64,                 // 8 bytes
+    pub fee_rate: u64,                   // 8 bytes - basis points, part of the pool seeds
+    pub tick_spacing: u16,               // 2 bytes
+    pub bump: u8,                        // 1 byte
+    pub token_a_vault: Pubkey,           // 32 bytes
+    pub token_b_vault: Pubkey,           // 32 bytes
+    pub token_a_hook_program: Option<Pubkey>, // 1 + 32 = 33 bytes
+    pub token_b_hook_program: Option<Pubkey>, // 1 + 32 = 33 bytes
+    pub token_a_flags: u8,               // 1 byte - MINT_FLAG_* bits
+    pub token_b_flags: u8,               // 1 byte - MINT_FLAG_* bits
+    pub sqrt_price_x64: u128,            // 16 bytes - sqrt(B per A) in Q64.64
+    pub current_tick: i32,               // 4 bytes
+    pub liquidity: u128,                 // 16 bytes - active at the current tick
+    pub fee_growth_global_a_x64: u128,   // 16 bytes - token A fees per unit of liquidity, Q64.64
+    pub fee_growth_global_b_x64: u128,   // 16 bytes - token B fees per unit of liquidity, Q64.64
+    pub paused: bool,                    // 1 byte - blocks swaps and deposits
+    pub emergency_mode: bool,            // 1 byte - also blocks them, and enables emergency_decrease_liquidity
+}
+
+impl ClPool {
+    pub const SPACE: usize = 32 + 32 + 32 + 8 + 8 + 2 + 1 + 32 + 32 + 33 + 33 + 1 + 1 + 16 + 4 + 16 + 16 + 16 + 1 + 1; // 317 bytes
+    
+    /// Swaps and deposits are refused while the pool is paused or in emergency mode
+    pub fn require_not_paused(&self) -> Result<()> {
+        require!(!self.paused && !self.emergency_mode, AMMError::PoolPaused);
+        Ok(())
+    }
+    
+    pub fn token_a_has_transfer_fee(&self) -> bool {
+        self.token_a_flags & MINT_FLAG_TRANSFER_FEE != 0
+    }
+    
+    pub fn token_b_has_transfer_fee(&self) -> bool {
+        self.token_b_flags & MINT_FLAG_TRANSFER_FEE != 0
+    }
+    
+    /// Whether the (input, output) mints charge transfer fees for the direction
+    pub fn transfer_fee_sides(&self, a_to_b: bool) -> (bool, bool) {
+        if a_to_b {
+            (self.token_a_has_transfer_fee(), self.token_b_has_transfer_fee())
+        } else {
+            (self.token_b_has_transfer_fee(), self.token_a_has_transfer_fee())
+        }
+    }
+    
+    /// Ticks must be in bounds, aligned to the tick spacing and ordered
+    pub fn validate_tick_range(&self, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        let spacing = self.tick_spacing as i32;
+        require!(
+            tick_lower < tick_upper
+                && tick_lower >= clmm_math::MIN_TICK
+                && tick_upper <= clmm_math::MAX_TICK
+                && tick_lower % spacing == 0
+                && tick_upper % spacing == 0,
+            AMMError::InvalidTickRange
+        );
+        Ok(())
+    }
+    
+    /// Track hook program changes made by the mint authorities since pool creation
+    pub fn refresh_hook_programs(&mut self, hook_a: Option<Pubkey>, hook_b: Option<Pubkey>) {
+        if self.token_a_hook_program != hook_a || self.token_b_hook_program != hook_b {
+            msg!("🔄 Hook programs changed: A={:?}, B={:?}", hook_a, hook_b);
+            self.token_a_hook_program = hook_a;
+            self.token_b_hook_program = hook_b;
+        }
+    }
+}
+
+/// Fixed-size run of `TICK_ARRAY_SIZE` ticks, `tick_spacing` apart
+#[account]
+pub struct TickArray {
+    pub pool: Pubkey,                    // 32 bytes
+    pub start_tick_index: i32,           // 4 bytes
+    pub ticks: [Tick; TICK_ARRAY_SIZE],  // 65 * 16 = 1040 bytes
+    pub bump: u8,                        // 1 byte
+}
 
-            invoke_signed(
-                &transfer_out_ix,
-                &[
-                    ctx.accounts.pool_token_a.to_account_info(),
-                    ctx.accounts.token_a_mint.to_account_info(),
-                    ctx.accounts.user_token_out.to_account_info(),
-                    ctx.accounts.pool.to_account_info(),
-                    ctx.accounts.token_2022_program.to_account_info(),
-                ],
-                pool_signer,
-            )?;
+impl TickArray {
+    pub const SPACE: usize = 32 + 4 + (Tick::SPACE * TICK_ARRAY_SIZE) + 1; // 1077 bytes
+    
+    /// Number of tick indexes one array spans
+    pub fn ticks_covered(tick_spacing: u16) -> i32 {
+        TICK_ARRAY_SIZE as i32 * tick_spacing as i32
+    }
+    
+    /// Start index of the array that holds `tick_index`
+    pub fn start_index_for(tick_index: i32, tick_spacing: u16) -> i32 {
+        let span = Self::ticks_covered(tick_spacing);
+        tick_index.div_euclid(span) * span
+    }
+    
+    /// Slot of `tick_index` in this array, if it is an aligned tick inside it
+    pub fn tick_offset(&self, tick_index: i32, tick_spacing: u16) -> Option<usize> {
+        let spacing = tick_spacing as i32;
+        let relative = tick_index.checked_sub(self.start_tick_index)?;
+        if relative < 0 || relative >= Self::ticks_covered(tick_spacing) || relative % spacing != 0 {
+            return None;
         }
-
-        // Update pool reserves
-        let pool = &mut ctx.accounts.pool;
+        Some((relative / spacing) as usize)
+    }
+    
+    pub fn tick_mut(&mut self, tick_index: i32, tick_spacing: u16) -> Option<&mut Tick> {
+        let offset = self.tick_offset(tick_index, tick_spacing)?;
+        Some(&mut self.ticks[offset])
+    }
+    
+    /// Nearest initialized tick at or below `tick_current` (`a_to_b`), or above it
+    pub fn next_initialized_tick(&self, tick_current: i32, tick_spacing: u16, a_to_b: bool) -> Option<i32> {
+        let indexes = self
+            .ticks
+            .iter()
+            .enumerate()
+            .filter(|(_, tick)| tick.initialized)
+            .map(|(i, _)| self.start_tick_index + i as i32 * tick_spacing as i32);
         if a_to_b {
-            pool.total_liquidity_a = pool.total_liquidity_a.checked_add(amount_in).unwrap();
-            pool.total_liquidity_b = pool.total_liquidity_b.checked_sub(amount_out).unwrap();
+            indexes.filter(|tick| *tick <= tick_current).max()
         } else {
-            pool.total_liquidity_b = pool.total_liquidity_b.checked_add(amount_in).unwrap();
-            pool.total_liquidity_a = pool.total_liquidity_a.checked_sub(amount_out).unwrap();
+            indexes.filter(|tick| *tick > tick_current).min()
         }
-        
-        msg!("💰 REAL Swap completed: {} output (fee: {})", amount_out, fee_amount);
-        msg!("📊 New reserves: A={}, B={}", pool.total_liquidity_a, pool.total_liquidity_b);
-        
-        Ok(amount_out)
     }
+}
 
-    /// Get pool information
-    pub fn get_pool_info(ctx: Context<GetPoolInfo>) -> Result<()> {
-        let pool = &ctx.accounts.pool;
-        
-        msg!("📋 Pool Information:");
-        msg!("🪙 Token A: {}", pool.token_a_mint);
-        msg!("🪙 Token B: {}", pool.token_b_mint);
-        msg!("💰 Liquidity A: {}", pool.total_liquidity_a);
-        msg!("💰 Liquidity B: {}", pool.total_liquidity_b);
-        msg!("🏷️  LP Supply: {}", pool.lp_token_supply);
-        msg!("💱 Current Price: {}", pool.current_price);
-        msg!("💸 Fee Rate: {}bp", pool.fee_rate);
-        msg!("🔗 Hook Enabled: {}", pool.hook_enabled);
-        
-        Ok(())
-    }
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct Tick {
+    pub initialized: bool,               // 1 byte
+    pub liquidity_net: i128,             // 16 bytes - added to active liquidity when crossed upwards
+    pub liquidity_gross: u128,           // 16 bytes - total liquidity of positions using the tick
+    pub fee_growth_outside_a_x64: u128,  // 16 bytes - growth on the other side of the tick from the price
+    pub fee_growth_outside_b_x64: u128,  // 16 bytes
 }
 
-/// Helper function to get mint decimals
-fn get_mint_decimals(mint_account: &UncheckedAccount) -> Result<u8> {
-    let mint_data = mint_account.try_borrow_data()?;
+impl Tick {
+    pub const SPACE: usize = 1 + 16 + 16 + 16 + 16; // 65 bytes
     
-    // Try to parse as StateWithExtensions first (for Token-2022 with extensions)
-    match StateWithExtensions::<Token2022Mint>::unpack(&mint_data) {
-        Ok(mint_with_extensions) => {
-            Ok(mint_with_extensions.base.decimals)
+    /// Apply a position's liquidity change at this tick; a tick left unused is cleared
+    pub fn update(
+        &mut self,
+        tick_index: i32,
+        tick_current: i32,
+        liquidity_delta: i128,
+        is_upper: bool,
+        fee_growth_global_a_x64: u128,
+        fee_growth_global_b_x64: u128,
+    ) -> Result<()> {
+        let liquidity_gross = clmm_math::add_liquidity_delta(self.liquidity_gross, liquidity_delta)?;
+        if liquidity_gross == 0 {
+            *self = Tick::default();
+            return Ok(());
         }
-        Err(_) => {
-            // Fallback to basic Token-2022 mint
-            match Token2022Mint::unpack(&mint_data) {
-                Ok(mint) => Ok(mint.decimals),
-                Err(_) => {
-                    msg!("❌ Failed to parse mint data");
-                    Err(AMMError::InvalidTokenProgram.into())
-                }
+        
+        if !self.initialized {
+            // By convention all growth before initialization happened below the tick
+            if tick_index <= tick_current {
+                self.fee_growth_outside_a_x64 = fee_growth_global_a_x64;
+                self.fee_growth_outside_b_x64 = fee_growth_global_b_x64;
             }
+            self.initialized = true;
         }
+        
+        self.liquidity_gross = liquidity_gross;
+        self.liquidity_net = if is_upper {
+            self.liquidity_net.checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net.checked_add(liquidity_delta)
+        }
+        .ok_or(AMMError::MathOverflow)?;
+        Ok(())
+    }
+    
+    /// Cross the tick during a swap: flip which side its outside growth refers to and
+    /// return the change to active liquidity
+    pub fn cross(&mut self, fee_growth_global_a_x64: u128, fee_growth_global_b_x64: u128, a_to_b: bool) -> i128 {
+        self.fee_growth_outside_a_x64 = fee_growth_global_a_x64.wrapping_sub(self.fee_growth_outside_a_x64);
+        self.fee_growth_outside_b_x64 = fee_growth_global_b_x64.wrapping_sub(self.fee_growth_outside_b_x64);
+        if a_to_b { -self.liquidity_net } else { self.liquidity_net }
     }
 }
 
-/// Helper function to check if mint has hooks
-fn check_mint_has_hooks(mint_account: &UncheckedAccount) -> Result<bool> {
-    let mint_data = mint_account.try_borrow_data()?;
-    let basic_mint_size = Token2022Mint::LEN;
-    Ok(mint_data.len() > basic_mint_size)
+/// Liquidity provided by one owner over `[tick_lower, tick_upper)` of a `ClPool`
+#[account]
+pub struct Position {
+    pub pool: Pubkey,                    // 32 bytes
+    pub owner: Pubkey,                   // 32 bytes
+    pub tick_lower: i32,                 // 4 bytes
+    pub tick_upper: i32,                 // 4 bytes
+    pub liquidity: u128,                 // 16 bytes
+    pub fee_growth_inside_a_last_x64: u128, // 16 bytes - snapshot at the last fee update
+    pub fee_growth_inside_b_last_x64: u128, // 16 bytes
+    pub fees_owed_a: u64,                // 8 bytes - earned (or left by an emergency exit), not yet collected
+    pub fees_owed_b: u64,                // 8 bytes
+    pub bump: u8,                        // 1 byte
 }
 
-/// Helper function to validate transfer hooks for REAL Token-2022
-fn validate_transfer_hooks_real(mint_account: &UncheckedAccount) -> Result<()> {
-    let has_hooks = check_mint_has_hooks(mint_account)?;
-    
-    if has_hooks {
-        msg!("🔗 Validating transfer hooks for mint: {}", mint_account.key());
-        // In production: would validate hook program is approved in registry
-        msg!("✅ Transfer hook validation passed");
-    } else {
-        msg!("ℹ️  No transfer hooks found for mint: {}", mint_account.key());
-    }
-    
-    Ok(())
+impl Position {
+    pub const SPACE: usize = 32 + 32 + 4 + 4 + 16 + 16 + 16 + 8 + 8 + 1; // 137 bytes
 }
 
-// ========== ACCOUNT STRUCTURES ==========
-
-#[derive(Accounts)]
-pub struct InitializeAMM<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + AMMConfig::SPACE,
-        seeds = [b"amm_config"],
-        bump
-    )]
-    pub amm_config: Account<'info, AMMConfig>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+/// Outcome of a concentrated-liquidity swap, in vault amounts
+pub struct ClSwapResult {
+    pub amount_in: u64,                  // consumed from the received input, fee included
+    pub amount_out: u64,                 // leaving the output vault
+    pub fee_amount: u64,
 }
 
-#[derive(Accounts)]
-pub struct SetHookRegistry<'info> {
-    #[account(mut)]
-    pub amm_config: Account<'info, AMMConfig>,
-    pub authority: Signer<'info>,
+/// Ticks per `TickArray`
+pub const TICK_ARRAY_SIZE: usize = 16;
+
+/// Largest tick spacing a concentrated pool may use
+pub const MAX_TICK_SPACING: u16 = 16_384;
+
+/// Index-style pool of 3-8 assets trading on the weighted-product invariant
+#[account]
+pub struct WeightedPool {
+    pub pool_id: [u8; 32],               // 32 bytes - WeightedPool::derive_id, part of the seeds
+    pub creator: Pubkey,                 // 32 bytes
+    pub created_at: i64,                 // 8 bytes
+    pub fee_rate: u64,                   // 8 bytes - basis points
+    pub bump: u8,                        // 1 byte
+    pub lp_mint: Pubkey,                 // 32 bytes
+    pub lp_token_supply: u64,            // 8 bytes - includes locked MINIMUM_LIQUIDITY
+    pub paused: bool,                    // 1 byte - blocks swaps and deposits
+    pub emergency_mode: bool,            // 1 byte - also blocks them, and enables emergency_exit_weighted_pool
+    pub assets: Vec<WeightedAsset>,      // 4 + (8 * 114) = 916 bytes
 }
 
-#[derive(Accounts)]
-pub struct CreatePool<'info> {
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + Pool::SPACE,
-        seeds = [
-            b"pool",
-            token_a_mint.key().as_ref(),
-            token_b_mint.key().as_ref()
-        ],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+impl WeightedPool {
+    pub const SPACE: usize = 32 + 32 + 8 + 8 + 1 + 32 + 8 + 1 + 1 + 4 + (WeightedAsset::SPACE * MAX_WEIGHTED_ASSETS); // 1039 bytes
     
-    #[account(mut)]
-    pub amm_config: Account<'info, AMMConfig>,
+    /// Swaps and deposits are refused while the pool is paused or in emergency mode
+    pub fn require_not_paused(&self) -> Result<()> {
+        require!(!self.paused && !self.emergency_mode, AMMError::PoolPaused);
+        Ok(())
+    }
     
-    /// CHECK: Token A mint (Token-2022)
-    pub token_a_mint: UncheckedAccount<'info>,
-    /// CHECK: Token B mint (Token-2022)
-    pub token_b_mint: UncheckedAccount<'info>,
+    /// Identifier of a basket: hash of its sorted mints, weights and fee rate
+    pub fn derive_id(mints: &[Pubkey], weights: &[u64], fee_rate: u64) -> [u8; 32] {
+        let weight_bytes: Vec<[u8; 8]> = weights.iter().map(|w| w.to_le_bytes()).collect();
+        let fee_rate_bytes = fee_rate.to_le_bytes();
+        let mut parts: Vec<&[u8]> = mints.iter().map(|m| m.as_ref()).collect();
+        parts.extend(weight_bytes.iter().map(|w| w.as_ref()));
+        parts.push(fee_rate_bytes.as_ref());
+        anchor_lang::solana_program::hash::hashv(&parts).to_bytes()
+    }
     
-    #[account(mut)]
-    pub creator: Signer<'info>,
+    /// Position of `mint` among the pool's assets
+    pub fn asset_index(&self, mint: &Pubkey) -> Result<usize> {
+        self.assets
+            .iter()
+            .position(|asset| asset.mint == *mint)
+            .ok_or_else(|| AMMError::InvalidTokenPair.into())
+    }
     
-    pub system_program: Program<'info, System>,
+    pub fn weights(&self) -> Vec<u64> {
+        self.assets.iter().map(|asset| asset.weight).collect()
+    }
 }
 
-#[derive(Accounts)]
-pub struct AddLiquidity<'info> {
-    #[account(
-        mut,
-        seeds = [
-            b"pool",
-            token_a_mint.key().as_ref(),
-            token_b_mint.key().as_ref()
-        ],
-        bump = pool.bump
-    )]
-    pub pool: Account<'info, Pool>,
-    
-    /// CHECK: Token A mint
-    pub token_a_mint: UncheckedAccount<'info>,
-    /// CHECK: Token B mint
-    pub token_b_mint: UncheckedAccount<'info>,
-    
-    /// CHECK: User's Token A account
-    #[account(mut)]
-    pub user_token_a: UncheckedAccount<'info>,
-    /// CHECK: User's Token B account
-    #[account(mut)]
-    pub user_token_b: UncheckedAccount<'info>,
-    
-    /// CHECK: Pool's Token A account
-    #[account(mut)]
-    pub pool_token_a: UncheckedAccount<'info>,
-    /// CHECK: Pool's Token B account
-    #[account(mut)]
-    pub pool_token_b: UncheckedAccount<'info>,
-    
-    pub user: Signer<'info>,
-    pub token_2022_program: Program<'info, Token2022>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WeightedAsset {
+    pub mint: Pubkey,                    // 32 bytes
+    pub vault: Pubkey,                   // 32 bytes
+    pub weight: u64,                     // 8 bytes - fixed point, weights sum to weighted_math::ONE
+    pub balance: u64,                    // 8 bytes
+    pub hook_program: Option<Pubkey>,    // 1 + 32 = 33 bytes
+    pub flags: u8,                       // 1 byte - MINT_FLAG_* bits
 }
 
-#[derive(Accounts)]
-pub struct Swap<'info> {
-    #[account(
-        mut,
-        seeds = [
-            b"pool",
-            token_a_mint.key().as_ref(),
-            token_b_mint.key().as_ref()
-        ],
-        bump = pool.bump
-    )]
-    pub pool: Account<'info, Pool>,
-    
-    /// CHECK: Token A mint
-    pub token_a_mint: UncheckedAccount<'info>,
-    /// CHECK: Token B mint
-    pub token_b_mint: UncheckedAccount<'info>,
-    
-    /// CHECK: User's input token account
-    #[account(mut)]
-    pub user_token_in: UncheckedAccount<'info>,
-    /// CHECK: User's output token account
-    #[account(mut)]
-    pub user_token_out: UncheckedAccount<'info>,
+impl WeightedAsset {
+    pub const SPACE: usize = 32 + 32 + 8 + 8 + 33 + 1; // 114 bytes
     
-    /// CHECK: Pool's Token A account
-    #[account(mut)]
-    pub pool_token_a: UncheckedAccount<'info>,
-    /// CHECK: Pool's Token B account
-    #[account(mut)]
-    pub pool_token_b: UncheckedAccount<'info>,
+    pub fn has_transfer_fee(&self) -> bool {
+        self.flags & MINT_FLAG_TRANSFER_FEE != 0
+    }
     
-    pub user: Signer<'info>,
-    pub token_2022_program: Program<'info, Token2022>,
+    /// Track hook program changes made by the mint authority since pool creation
+    pub fn refresh_hook_program(&mut self, hook_program: Option<Pubkey>) {
+        if self.hook_program != hook_program {
+            msg!("🔄 Hook program changed for {}: {:?}", self.mint, hook_program);
+            self.hook_program = hook_program;
+        }
+    }
 }
 
-#[derive(Accounts)]
-pub struct GetPoolInfo<'info> {
-    pub pool: Account<'info, Pool>,
+/// Asset count bounds for weighted pools
+pub const MIN_WEIGHTED_ASSETS: usize = 3;
+pub const MAX_WEIGHTED_ASSETS: usize = 8;
+
+/// Accounts per asset in weighted join/exit remaining accounts: mint, vault, user token account
+pub const WEIGHTED_ASSET_ACCOUNTS: usize = 3;
+
+/// Maximum number of pools a route may pass through
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+/// Fixed accounts per route hop, before the hook groups
+pub const ROUTE_HOP_ACCOUNTS: usize = 6;
+
+/// Mint extension flags recorded on each side of a pool
+pub const MINT_FLAG_TRANSFER_HOOK: u8 = 1 << 0;
+pub const MINT_FLAG_TRANSFER_FEE: u8 = 1 << 1;
+pub const MINT_FLAG_PERMANENT_DELEGATE: u8 = 1 << 2;
+pub const MINT_FLAG_NON_TRANSFERABLE: u8 = 1 << 3;
+
+/// LP tokens permanently locked by the first deposit (never minted)
+pub const MINIMUM_LIQUIDITY: u64 = 1000;
+
+/// Decimals of every pool's LP mint
+pub const LP_DECIMALS: u8 = 9;
+
+// Events
+#[event]
+pub struct HookRegistryUpdatedEvent {
+    pub old_hook_registry: Option<Pubkey>,
+    pub new_hook_registry: Pubkey,
+    pub timestamp: i64,
 }
 
-// Data structures
-#[account]
-pub struct AMMConfig {
-    pub authority: Pubkey,                // 32 bytes
-    pub fee_rate: u64,                    // 8 bytes - basis points
-    pub total_pools: u32,                 // 4 bytes
-    pub created_at: i64,                  // 8 bytes
-    pub hook_registry: Option<Pubkey>,    // 1 + 32 = 33 bytes
+#[event]
+pub struct AdminRoleUpdatedEvent {
+    pub role: AdminRole,
+    pub old_account: Pubkey,
+    pub new_account: Pubkey,
+    pub timestamp: i64,
 }
 
-impl AMMConfig {
-    pub const SPACE: usize = 32 + 8 + 4 + 8 + 33; // 85 bytes
+#[event]
+pub struct OwnershipTransferStartedEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
 }
 
-#[account]
-pub struct Pool {
-    pub token_a_mint: Pubkey,            // 32 bytes
-    pub token_b_mint: Pubkey,            // 32 bytes
-    pub creator: Pubkey,                 // 32 bytes
-    pub created_at: i64,                 // 8 bytes
-    pub fee_rate: u64,                   // 8 bytes
-    pub current_price: u64,              // 8 bytes
-    pub total_liquidity_a: u64,          // 8 bytes
-    pub total_liquidity_b: u64,          // 8 bytes
-    pub lp_token_supply: u64,            // 8 bytes
-    pub hook_enabled: bool,              // 1 byte
-    pub token_program_id: Pubkey,        // 32 bytes
-    pub bump: u8,                        // 1 byte
+#[event]
+pub struct OwnershipTransferredEvent {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
 }
 
-impl Pool {
-    pub const SPACE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 32 + 1; // 178 bytes
+#[event]
+pub struct AMMPauseUpdatedEvent {
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HookProgramPauseUpdatedEvent {
+    pub hook_program: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolStatusUpdatedEvent {
+    pub pool: Pubkey,
+    pub paused: bool,
+    pub emergency_mode: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub lp_amount: u64,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyPositionExitEvent {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub liquidity: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWeightedExitEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub lp_amount: u64,
+    pub amounts_out: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyClaimPaidEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amounts: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeTierAddedEvent {
+    pub fee_rate: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeTierRemovedEvent {
+    pub fee_rate: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeeUpdatedEvent {
+    pub old_protocol_fee_rate: u64,
+    pub new_protocol_fee_rate: u64,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeesCollectedEvent {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AmpRampStartedEvent {
+    pub pool: Pubkey,
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start: i64,
+    pub ramp_end: i64,
+}
+
+#[event]
+pub struct AmpRampStoppedEvent {
+    pub pool: Pubkey,
+    pub amp: u64,
+    pub timestamp: i64,
 }
 
 #[error_code]
@@ -609,6 +5021,186 @@ pub enum AMMError {
     InvalidTokenProgram,
     #[msg("Slippage tolerance exceeded")]
     SlippageExceeded,
-    #[msg("Pool already exists")]
-    PoolAlreadyExists,
-}
\ No newline at end of file
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Vault does not belong to this pool")]
+    InvalidVault,
+    #[msg("Token account mint or owner mismatch")]
+    InvalidTokenAccount,
+    #[msg("Transfer hook accounts missing from remaining accounts")]
+    MissingHookAccounts,
+    #[msg("Mint extension not supported by pools")]
+    UnsupportedMintExtension,
+    #[msg("Fee rate exceeds 10000 basis points")]
+    InvalidFeeRate,
+    #[msg("Fee tier not enabled")]
+    InvalidFeeTier,
+    #[msg("Fee tier already exists")]
+    FeeTierAlreadyExists,
+    #[msg("Too many fee tiers")]
+    TooManyFeeTiers,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("No protocol fees to collect")]
+    NoProtocolFees,
+    #[msg("Invalid observation window")]
+    InvalidObservationWindow,
+    #[msg("Not enough observation history for the requested window")]
+    InsufficientObservationHistory,
+    #[msg("Invalid swap route")]
+    InvalidRoute,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Invalid amplification coefficient or ramp")]
+    InvalidAmplification,
+    #[msg("Operation not supported for this pool type")]
+    InvalidPoolType,
+    #[msg("StableSwap mints must have the same decimals")]
+    StableDecimalsMismatch,
+    #[msg("Tick out of range, unaligned to the tick spacing, or lower >= upper")]
+    InvalidTickRange,
+    #[msg("Invalid tick spacing")]
+    InvalidTickSpacing,
+    #[msg("Sqrt price out of range")]
+    InvalidSqrtPrice,
+    #[msg("Tick array does not belong to this pool or does not cover the tick")]
+    InvalidTickArray,
+    #[msg("Swap crossed past the supplied tick arrays")]
+    TickArrayMissing,
+    #[msg("Position still holds liquidity or uncollected fees")]
+    PositionNotEmpty,
+    #[msg("No fees owed to the position")]
+    NoFeesOwed,
+    #[msg("Weights must each be at least 1% and sum to 1e18")]
+    InvalidWeights,
+    #[msg("Amount exceeds the weighted pool's maximum in/out ratio")]
+    WeightedRatioExceeded,
+    #[msg("Weighted pools hold 3 to 8 assets, with accounts for each")]
+    InvalidAssetCount,
+    #[msg("AMM is paused")]
+    AMMPaused,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Transfer hook program is paused")]
+    HookProgramPaused,
+    #[msg("Hook program already paused")]
+    HookProgramAlreadyPaused,
+    #[msg("Hook program not paused")]
+    HookProgramNotPaused,
+    #[msg("Too many paused hook programs")]
+    TooManyPausedHookPrograms,
+    #[msg("Pool is not in emergency mode")]
+    NotInEmergencyMode,
+    #[msg("Emergency claim account required")]
+    EmergencyClaimRequired,
+    #[msg("Nothing owed to claim")]
+    NothingToClaim,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick_array_with(start_tick_index: i32, tick_spacing: u16, initialized: &[i32]) -> TickArray {
+        let mut tick_array = TickArray {
+            pool: Pubkey::default(),
+            start_tick_index,
+            ticks: [Tick::default(); TICK_ARRAY_SIZE],
+            bump: 0,
+        };
+        for tick_index in initialized {
+            tick_array.tick_mut(*tick_index, tick_spacing).unwrap().initialized = true;
+        }
+        tick_array
+    }
+
+    #[test]
+    fn next_initialized_tick_in_each_direction() {
+        let tick_array = tick_array_with(-80, 10, &[-10, 10]);
+
+        // Downwards the current tick itself counts, upwards it does not
+        assert_eq!(tick_array.next_initialized_tick(0, 10, true), Some(-10));
+        assert_eq!(tick_array.next_initialized_tick(-10, 10, true), Some(-10));
+        assert_eq!(tick_array.next_initialized_tick(-11, 10, true), None);
+        assert_eq!(tick_array.next_initialized_tick(0, 10, false), Some(10));
+        assert_eq!(tick_array.next_initialized_tick(10, 10, false), None);
+
+        assert_eq!(TickArray::start_index_for(-1, 10), -160);
+        assert_eq!(tick_array.tick_offset(-80, 10), Some(0));
+        assert_eq!(tick_array.tick_offset(-75, 10), None);
+        assert_eq!(tick_array.tick_offset(80, 10), None);
+    }
+
+    #[test]
+    fn crossing_ticks_tracks_liquidity_and_fee_growth_inside() {
+        let (tick_lower, tick_upper, liquidity) = (-10, 10, 1_000_000i128);
+        let mut lower = Tick::default();
+        let mut upper = Tick::default();
+        let mut global = 0u128;
+
+        // Opened with the price inside the range at tick 0
+        lower.update(tick_lower, 0, liquidity, false, global, 0).unwrap();
+        upper.update(tick_upper, 0, liquidity, true, global, 0).unwrap();
+        let mut active = liquidity as u128;
+        let inside = |tick_current: i32, global: u128, lower: &Tick, upper: &Tick| {
+            clmm_math::fee_growth_inside(
+                tick_lower,
+                tick_upper,
+                tick_current,
+                global,
+                lower.fee_growth_outside_a_x64,
+                upper.fee_growth_outside_a_x64,
+            )
+        };
+
+        // Fees while inside count for the position
+        global += 100;
+        assert_eq!(inside(0, global, &lower, &upper), 100);
+
+        // Price moves up through the upper tick: liquidity leaves, fees above don't count
+        let delta = upper.cross(global, 0, false);
+        active = clmm_math::add_liquidity_delta(active, delta).unwrap();
+        assert_eq!(active, 0);
+        global += 50;
+        assert_eq!(inside(tick_upper, global, &lower, &upper), 100);
+
+        // Back down through both ticks: liquidity returns, then leaves again below
+        let delta = upper.cross(global, 0, true);
+        active = clmm_math::add_liquidity_delta(active, delta).unwrap();
+        assert_eq!(active, liquidity as u128);
+        assert_eq!(inside(0, global, &lower, &upper), 100);
+        let delta = lower.cross(global, 0, true);
+        active = clmm_math::add_liquidity_delta(active, delta).unwrap();
+        assert_eq!(active, 0);
+        global += 30;
+        assert_eq!(inside(tick_lower - 1, global, &lower, &upper), 100);
+
+        // Removing all liquidity clears the ticks
+        lower.update(tick_lower, tick_lower - 1, -liquidity, false, global, 0).unwrap();
+        assert!(!lower.initialized && lower.liquidity_gross == 0);
+    }
+
+    #[test]
+    fn emergency_claim_accumulates_and_pays_selected_assets() {
+        let mut claim = EmergencyClaim {
+            pool: Pubkey::default(),
+            owner: Pubkey::default(),
+            amounts: Vec::new(),
+            bump: 0,
+        };
+
+        // Two skipped exits add up per asset
+        claim.add(&[0, 500]).unwrap();
+        claim.add(&[200, 300]).unwrap();
+        assert_eq!(claim.amounts, vec![200, 800]);
+
+        // A side still skipped stays owed
+        assert_eq!(claim.take(&[false, true]), vec![200, 0]);
+        assert_eq!(claim.amounts, vec![0, 800]);
+        assert_eq!(claim.take(&[false, false]), vec![0, 800]);
+        assert_eq!(claim.take(&[false, false]), vec![0, 0]);
+
+        claim.add(&[u64::MAX, 0]).unwrap();
+        assert!(claim.add(&[1, 0]).is_err());
+    }
+}
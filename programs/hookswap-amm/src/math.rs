@@ -0,0 +1,329 @@
+//! Constant-product curve math.
+//!
+//! Pure integer math with u128 intermediates and explicit rounding: amounts
+//! paid out by the pool round down, amounts owed to the pool round up. No
+//! account or runtime access, so off-chain quoting services can use it
+//! directly through the `no-entrypoint` feature.
+
+use crate::{AMMError, MINIMUM_LIQUIDITY};
+
+/// Fee rates are expressed in basis points of this denominator
+pub const FEE_RATE_DENOMINATOR: u64 = 10_000;
+
+/// Prices are quote-per-base ratios scaled by this factor (matches `Pool.current_price`)
+pub const PRICE_SCALE: u128 = 1_000_000_000;
+
+/// Result of an exact-input swap against the curve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub fee_amount: u64,
+    pub amount_out: u64,
+}
+
+/// Result of an exact-output swap against the curve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExactOutQuote {
+    pub amount_in: u64,
+    pub fee_amount: u64,
+}
+
+/// `a * b / c`, rounded down
+pub fn mul_div_floor(a: u64, b: u64, c: u64) -> Result<u64, AMMError> {
+    if c == 0 {
+        return Err(AMMError::MathOverflow);
+    }
+    let result = (a as u128)
+        .checked_mul(b as u128)
+        .ok_or(AMMError::MathOverflow)?
+        / c as u128;
+    u64::try_from(result).map_err(|_| AMMError::MathOverflow)
+}
+
+/// `a * b / c`, rounded up
+pub fn mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64, AMMError> {
+    if c == 0 {
+        return Err(AMMError::MathOverflow);
+    }
+    let result = (a as u128)
+        .checked_mul(b as u128)
+        .ok_or(AMMError::MathOverflow)?
+        .div_ceil(c as u128);
+    u64::try_from(result).map_err(|_| AMMError::MathOverflow)
+}
+
+/// Spot price of the base token in quote tokens, scaled by `PRICE_SCALE` and rounded down
+pub fn spot_price(reserve_base: u64, reserve_quote: u64) -> Result<u128, AMMError> {
+    if reserve_base == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    Ok(reserve_quote as u128 * PRICE_SCALE / reserve_base as u128)
+}
+
+/// Time-weighted average price between two cumulative price readings.
+///
+/// Accumulators wrap on overflow, so the difference is taken with wrapping arithmetic.
+pub fn twap(
+    cumulative_start: u128,
+    cumulative_end: u128,
+    elapsed_seconds: i64,
+) -> Result<u128, AMMError> {
+    if elapsed_seconds <= 0 {
+        return Err(AMMError::InvalidObservationWindow);
+    }
+    Ok(cumulative_end.wrapping_sub(cumulative_start) / elapsed_seconds as u128)
+}
+
+/// Integer square root, rounded down (Newton's method)
+pub fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+/// Swap fee charged on `amount_in`, rounded up in favour of the pool
+pub fn swap_fee(amount_in: u64, fee_rate: u64) -> Result<u64, AMMError> {
+    if fee_rate > FEE_RATE_DENOMINATOR {
+        return Err(AMMError::InvalidFeeRate);
+    }
+    mul_div_ceil(amount_in, fee_rate, FEE_RATE_DENOMINATOR)
+}
+
+/// Protocol share of a swap fee (`protocol_fee_rate` in basis points of the fee), rounded down
+pub fn protocol_fee(fee_amount: u64, protocol_fee_rate: u64) -> Result<u64, AMMError> {
+    if protocol_fee_rate > FEE_RATE_DENOMINATOR {
+        return Err(AMMError::InvalidFeeRate);
+    }
+    mul_div_floor(fee_amount, protocol_fee_rate, FEE_RATE_DENOMINATOR)
+}
+
+/// Output for `amount_in` (after fees) on `x * y = k`, rounded down
+pub fn constant_product_amount_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+) -> Result<u64, AMMError> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    let denominator = reserve_in
+        .checked_add(amount_in)
+        .ok_or(AMMError::MathOverflow)?;
+    mul_div_floor(reserve_out, amount_in, denominator)
+}
+
+/// Exact-input swap: fee first, then the curve on the remainder
+pub fn swap_exact_in(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_rate: u64,
+) -> Result<SwapQuote, AMMError> {
+    let fee_amount = swap_fee(amount_in, fee_rate)?;
+    let amount_in_after_fee = amount_in
+        .checked_sub(fee_amount)
+        .ok_or(AMMError::MathOverflow)?;
+    let amount_out = constant_product_amount_out(amount_in_after_fee, reserve_in, reserve_out)?;
+    Ok(SwapQuote { fee_amount, amount_out })
+}
+
+/// Input (after fees) needed for `amount_out` on `x * y = k`, rounded up
+pub fn constant_product_amount_in(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+) -> Result<u64, AMMError> {
+    if reserve_in == 0 || amount_out >= reserve_out {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    mul_div_ceil(reserve_in, amount_out, reserve_out - amount_out)
+}
+
+/// Smallest input whose remainder after `swap_fee` covers `amount_after_fee`
+pub fn amount_before_fee(amount_after_fee: u64, fee_rate: u64) -> Result<u64, AMMError> {
+    if fee_rate >= FEE_RATE_DENOMINATOR {
+        return Err(AMMError::InvalidFeeRate);
+    }
+    let mut amount = mul_div_ceil(amount_after_fee, FEE_RATE_DENOMINATOR, FEE_RATE_DENOMINATOR - fee_rate)?;
+    // The fee itself rounds up, which can leave the remainder one short
+    while amount - swap_fee(amount, fee_rate)? < amount_after_fee {
+        amount = amount.checked_add(1).ok_or(AMMError::MathOverflow)?;
+    }
+    Ok(amount)
+}
+
+/// Exact-output swap: curve input for `amount_out`, then grossed up for the fee
+pub fn swap_exact_out(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_rate: u64,
+) -> Result<ExactOutQuote, AMMError> {
+    let amount_in_after_fee = constant_product_amount_in(amount_out, reserve_in, reserve_out)?;
+    let amount_in = amount_before_fee(amount_in_after_fee, fee_rate)?;
+    let fee_amount = swap_fee(amount_in, fee_rate)?;
+    Ok(ExactOutQuote { amount_in, fee_amount })
+}
+
+/// Shortfall of the execution price against the spot price, in basis points
+pub fn price_impact_bps(
+    amount_in: u64,
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+) -> Result<u64, AMMError> {
+    // spot: reserve_out / reserve_in, execution: amount_out / amount_in
+    let spot_value = (amount_in as u128) * (reserve_out as u128);
+    if spot_value == 0 {
+        return Ok(0);
+    }
+    let execution_value = (amount_out as u128) * (reserve_in as u128);
+    let shortfall = spot_value.saturating_sub(execution_value);
+    let impact = shortfall * FEE_RATE_DENOMINATOR as u128 / spot_value;
+    u64::try_from(impact).map_err(|_| AMMError::MathOverflow)
+}
+
+/// Price impact in basis points against a precomputed spot price (scaled by
+/// `PRICE_SCALE`), for curves whose spot price is not the reserve ratio
+pub fn price_impact_bps_at_spot(amount_in: u64, amount_out: u64, spot_price: u128) -> Result<u64, AMMError> {
+    let spot_value = (amount_in as u128)
+        .checked_mul(spot_price)
+        .ok_or(AMMError::MathOverflow)?;
+    if spot_value == 0 {
+        return Ok(0);
+    }
+    let execution_value = amount_out as u128 * PRICE_SCALE;
+    let shortfall = spot_value.saturating_sub(execution_value);
+    let impact = shortfall * FEE_RATE_DENOMINATOR as u128 / spot_value;
+    u64::try_from(impact).map_err(|_| AMMError::MathOverflow)
+}
+
+/// LP tokens for the first deposit: `sqrt(a * b)` minus the locked `MINIMUM_LIQUIDITY`
+pub fn initial_lp_tokens(amount_a: u64, amount_b: u64) -> Result<u64, AMMError> {
+    let product = (amount_a as u128)
+        .checked_mul(amount_b as u128)
+        .ok_or(AMMError::MathOverflow)?;
+    let liquidity = u64::try_from(integer_sqrt(product)).map_err(|_| AMMError::MathOverflow)?;
+    liquidity
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .filter(|lp| *lp > 0)
+        .ok_or(AMMError::InsufficientLiquidity)
+}
+
+/// LP tokens for a later deposit, proportional to the smaller side, rounded down
+pub fn proportional_lp_tokens(
+    amount_a: u64,
+    amount_b: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+) -> Result<u64, AMMError> {
+    if reserve_a == 0 || reserve_b == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    let lp_from_a = mul_div_floor(amount_a, lp_supply, reserve_a)?;
+    let lp_from_b = mul_div_floor(amount_b, lp_supply, reserve_b)?;
+    Ok(lp_from_a.min(lp_from_b))
+}
+
+/// Pro-rata share of a reserve for `lp_amount`, rounded down
+pub fn withdraw_amount(lp_amount: u64, reserve: u64, lp_supply: u64) -> Result<u64, AMMError> {
+    if lp_supply == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    mul_div_floor(lp_amount, reserve, lp_supply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounds_down_and_up() {
+        assert_eq!(mul_div_floor(10, 10, 3).unwrap(), 33);
+        assert_eq!(mul_div_ceil(10, 10, 3).unwrap(), 34);
+        // Exact results are the same either way
+        assert_eq!(mul_div_floor(10, 9, 3).unwrap(), 30);
+        assert_eq!(mul_div_ceil(10, 9, 3).unwrap(), 30);
+        assert!(matches!(mul_div_floor(1, 1, 0), Err(AMMError::MathOverflow)));
+        assert!(matches!(mul_div_ceil(1, 1, 0), Err(AMMError::MathOverflow)));
+    }
+
+    #[test]
+    fn mul_div_uses_a_u128_intermediate() {
+        assert_eq!(mul_div_floor(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(mul_div_ceil(u64::MAX, 2, 4).unwrap(), u64::MAX / 2 + 1);
+        assert!(matches!(mul_div_floor(u64::MAX, 2, 1), Err(AMMError::MathOverflow)));
+    }
+
+    #[test]
+    fn swap_fee_rounds_up() {
+        // 0.3% of 1 is 0.003, charged as a whole unit
+        assert_eq!(swap_fee(1, 30).unwrap(), 1);
+        assert_eq!(swap_fee(10_000, 30).unwrap(), 30);
+        assert_eq!(swap_fee(10_001, 30).unwrap(), 31);
+        assert_eq!(swap_fee(1_000, 0).unwrap(), 0);
+        assert!(matches!(swap_fee(1, FEE_RATE_DENOMINATOR + 1), Err(AMMError::InvalidFeeRate)));
+    }
+
+    #[test]
+    fn amount_before_fee_is_the_smallest_sufficient_input() {
+        for fee_rate in [0, 1, 30, 100, 2_500, 9_999] {
+            for amount_after_fee in [1, 2, 3, 997, 1_000, 123_456_789] {
+                let amount = amount_before_fee(amount_after_fee, fee_rate).unwrap();
+                assert!(amount - swap_fee(amount, fee_rate).unwrap() >= amount_after_fee);
+                let less = amount - 1;
+                assert!(less - swap_fee(less, fee_rate).unwrap() < amount_after_fee);
+            }
+        }
+        assert!(matches!(amount_before_fee(1, FEE_RATE_DENOMINATOR), Err(AMMError::InvalidFeeRate)));
+    }
+
+    #[test]
+    fn exact_out_input_covers_what_exact_in_needs() {
+        let (reserve_in, reserve_out) = (1_000_000_000, 2_000_000_000);
+        for fee_rate in [0, 30, 100] {
+            for amount_out in [1, 1_000, 999_999, 500_000_000] {
+                let quote = swap_exact_out(amount_out, reserve_in, reserve_out, fee_rate).unwrap();
+                assert_eq!(quote.fee_amount, swap_fee(quote.amount_in, fee_rate).unwrap());
+                // Paying the quoted input yields at least the requested output...
+                let out = swap_exact_in(quote.amount_in, reserve_in, reserve_out, fee_rate).unwrap();
+                assert!(out.amount_out >= amount_out);
+                // ...and one unit less does not
+                let short = swap_exact_in(quote.amount_in - 1, reserve_in, reserve_out, fee_rate).unwrap();
+                assert!(short.amount_out < amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn swaps_never_lower_k() {
+        let (reserve_in, reserve_out) = (1_234_567, 7_654_321);
+        let quote = swap_exact_in(100_000, reserve_in, reserve_out, 30).unwrap();
+        let k_before = reserve_in as u128 * reserve_out as u128;
+        let k_after = (reserve_in + 100_000 - quote.fee_amount) as u128 * (reserve_out - quote.amount_out) as u128;
+        assert!(k_after >= k_before);
+    }
+
+    #[test]
+    fn max_reserves_do_not_panic() {
+        // Small trades against u64::MAX reserves stay within the u128 intermediates
+        let quote = swap_exact_in(1_000_000, u64::MAX / 2, u64::MAX, 30).unwrap();
+        assert!(quote.amount_out > 0);
+        assert!(swap_exact_out(1_000_000, u64::MAX / 2, u64::MAX, 30).is_ok());
+        // Reserves that would pass u64::MAX are rejected, not wrapped
+        assert!(matches!(swap_exact_in(2, u64::MAX, u64::MAX, 0), Err(AMMError::MathOverflow)));
+        assert!(matches!(
+            swap_exact_out(u64::MAX - 1, u64::MAX, u64::MAX, 0),
+            Err(AMMError::MathOverflow)
+        ));
+        assert_eq!(withdraw_amount(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(initial_lp_tokens(u64::MAX, u64::MAX).unwrap(), u64::MAX - MINIMUM_LIQUIDITY);
+    }
+}
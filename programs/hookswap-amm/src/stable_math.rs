@@ -0,0 +1,417 @@
+//! StableSwap curve math for two-token pools.
+//!
+//! Curve-style invariant `A·n^n·Σx + D = A·D·n^n + D^(n+1) / (n^n·Πx)` with
+//! n = 2, solved with Newton's method in u128. Like `math`, it is pure and
+//! rounds in favour of the pool, so it can be used off-chain for quoting.
+
+use crate::clmm_math::mul_div;
+use crate::math::{self, ExactOutQuote, SwapQuote};
+use crate::{AMMError, MINIMUM_LIQUIDITY};
+
+/// Number of tokens in a pool
+const N_COINS: u128 = 2;
+
+/// Newton iterations before giving up on convergence
+const MAX_ITERATIONS: usize = 255;
+
+/// Amplification coefficient bounds
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Largest factor a single ramp may change the amplification by
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Shortest allowed amplification ramp
+pub const MIN_RAMP_DURATION: i64 = 24 * 60 * 60;
+
+/// Amplification at `now` for a linear ramp from `amp_initial` to `amp_target`
+pub fn current_amp(amp_initial: u64, amp_target: u64, ramp_start: i64, ramp_end: i64, now: i64) -> u64 {
+    if now >= ramp_end || ramp_end <= ramp_start {
+        return amp_target;
+    }
+    if now <= ramp_start {
+        return amp_initial;
+    }
+    let elapsed = (now - ramp_start) as u128;
+    let duration = (ramp_end - ramp_start) as u128;
+    if amp_target >= amp_initial {
+        amp_initial + ((amp_target - amp_initial) as u128 * elapsed / duration) as u64
+    } else {
+        amp_initial - ((amp_initial - amp_target) as u128 * elapsed / duration) as u64
+    }
+}
+
+/// Invariant D for the given reserves
+pub fn compute_d(reserve_a: u64, reserve_b: u64, amp: u64) -> Result<u128, AMMError> {
+    let x = reserve_a as u128;
+    let y = reserve_b as u128;
+    let sum = x + y;
+    if sum == 0 {
+        return Ok(0);
+    }
+    if x == 0 || y == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+
+    let ann = amp as u128 * N_COINS * N_COINS;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // d_p = D^3 / (n^n · x · y)
+        let d_p = d
+            .checked_mul(d)
+            .map(|v| v / (x * N_COINS))
+            .and_then(|v| v.checked_mul(d))
+            .map(|v| v / (y * N_COINS))
+            .ok_or(AMMError::MathOverflow)?;
+        let d_prev = d;
+        let numerator = ann
+            .checked_mul(sum)
+            .and_then(|v| v.checked_add(d_p * N_COINS))
+            .and_then(|v| v.checked_mul(d))
+            .ok_or(AMMError::MathOverflow)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)
+            .and_then(|v| v.checked_add((N_COINS + 1) * d_p))
+            .ok_or(AMMError::MathOverflow)?;
+        d = numerator / denominator;
+        if d.abs_diff(d_prev) <= 1 {
+            return Ok(d);
+        }
+    }
+    Err(AMMError::MathOverflow)
+}
+
+/// Reserve of the other token that keeps the invariant at `d` when one reserve is `x`
+pub fn compute_y(x: u128, d: u128, amp: u64) -> Result<u128, AMMError> {
+    if x == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    let ann = amp as u128 * N_COINS * N_COINS;
+
+    // c = D^3 / (n^n · x · Ann), b = x + D / Ann
+    let c = d
+        .checked_mul(d)
+        .map(|v| v / (x * N_COINS))
+        .and_then(|v| v.checked_mul(d))
+        .map(|v| v / (ann * N_COINS))
+        .ok_or(AMMError::MathOverflow)?;
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let numerator = y
+            .checked_mul(y)
+            .and_then(|v| v.checked_add(c))
+            .ok_or(AMMError::MathOverflow)?;
+        let denominator = (2 * y + b)
+            .checked_sub(d)
+            .filter(|v| *v > 0)
+            .ok_or(AMMError::MathOverflow)?;
+        y = numerator / denominator;
+        if y.abs_diff(y_prev) <= 1 {
+            return Ok(y);
+        }
+    }
+    Err(AMMError::MathOverflow)
+}
+
+/// Spot price of the input token in output tokens, scaled by `PRICE_SCALE` and rounded down.
+///
+/// This is `-dy/dx` along the invariant: `(Ann·x + d_p)·y / ((Ann·y + d_p)·x)` with
+/// `d_p = D^3 / (n^n·x·y)`, which tends to `y / x` as A → 0 and to 1 as A → ∞.
+pub fn spot_price(reserve_in: u64, reserve_out: u64, amp: u64) -> Result<u128, AMMError> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    let x = reserve_in as u128;
+    let y = reserve_out as u128;
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let ann = amp as u128 * N_COINS * N_COINS;
+    let d_p = d
+        .checked_mul(d)
+        .map(|v| v / (x * N_COINS))
+        .and_then(|v| v.checked_mul(d))
+        .map(|v| v / (y * N_COINS))
+        .ok_or(AMMError::MathOverflow)?;
+    let numerator = ann.checked_mul(x).and_then(|v| v.checked_add(d_p)).ok_or(AMMError::MathOverflow)?;
+    let denominator = ann.checked_mul(y).and_then(|v| v.checked_add(d_p)).ok_or(AMMError::MathOverflow)?;
+    let ratio = mul_div(numerator, math::PRICE_SCALE, denominator, false)?;
+    mul_div(ratio, y, x, false)
+}
+
+/// Exact-input swap: fee first, then the invariant on the remainder; output rounds down
+pub fn swap_exact_in(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_rate: u64,
+    amp: u64,
+) -> Result<SwapQuote, AMMError> {
+    let fee_amount = math::swap_fee(amount_in, fee_rate)?;
+    let amount_in_after_fee = amount_in
+        .checked_sub(fee_amount)
+        .ok_or(AMMError::MathOverflow)?;
+
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let new_reserve_in = reserve_in as u128 + amount_in_after_fee as u128;
+    let new_reserve_out = compute_y(new_reserve_in, d, amp)?;
+    // One unit kept back to absorb Newton rounding
+    let amount_out = (reserve_out as u128)
+        .saturating_sub(new_reserve_out)
+        .saturating_sub(1);
+
+    Ok(SwapQuote {
+        fee_amount,
+        amount_out: u64::try_from(amount_out).map_err(|_| AMMError::MathOverflow)?,
+    })
+}
+
+/// Exact-output swap: invariant input for `amount_out` rounded up, then grossed up for the fee
+pub fn swap_exact_out(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_rate: u64,
+    amp: u64,
+) -> Result<ExactOutQuote, AMMError> {
+    if amount_out >= reserve_out {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let new_reserve_out = (reserve_out - amount_out) as u128;
+    let new_reserve_in = compute_y(new_reserve_out, d, amp)?;
+    let amount_in_after_fee = new_reserve_in
+        .saturating_sub(reserve_in as u128)
+        .checked_add(1)
+        .ok_or(AMMError::MathOverflow)?;
+    let amount_in_after_fee =
+        u64::try_from(amount_in_after_fee).map_err(|_| AMMError::MathOverflow)?;
+
+    let amount_in = math::amount_before_fee(amount_in_after_fee, fee_rate)?;
+    let fee_amount = math::swap_fee(amount_in, fee_rate)?;
+    Ok(ExactOutQuote { amount_in, fee_amount })
+}
+
+/// Imbalanced deposits pay `fee_rate * n / (4 * (n - 1))` on their distance from a
+/// proportional one, as in Curve; for n = 2 that is half the swap fee
+fn imbalance_fee(ideal_reserve: u128, new_reserve: u64, fee_rate: u64) -> Result<u128, AMMError> {
+    let difference = ideal_reserve.abs_diff(new_reserve as u128);
+    mul_div(
+        difference,
+        fee_rate as u128 * N_COINS,
+        math::FEE_RATE_DENOMINATOR as u128 * 4 * (N_COINS - 1),
+        true,
+    )
+}
+
+/// LP tokens for a deposit, from the growth of D after the imbalance fee; the
+/// first deposit locks `MINIMUM_LIQUIDITY`. The fee stays in the reserves, so a
+/// one-sided deposit followed by a proportional withdrawal costs about as much as a swap.
+pub fn deposit_lp_tokens(
+    amount_a: u64,
+    amount_b: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+    amp: u64,
+    fee_rate: u64,
+) -> Result<u64, AMMError> {
+    let new_reserve_a = reserve_a.checked_add(amount_a).ok_or(AMMError::MathOverflow)?;
+    let new_reserve_b = reserve_b.checked_add(amount_b).ok_or(AMMError::MathOverflow)?;
+    let d1 = compute_d(new_reserve_a, new_reserve_b, amp)?;
+
+    if lp_supply == 0 {
+        let liquidity = u64::try_from(d1).map_err(|_| AMMError::MathOverflow)?;
+        return liquidity
+            .checked_sub(MINIMUM_LIQUIDITY)
+            .filter(|lp| *lp > 0)
+            .ok_or(AMMError::InsufficientLiquidity);
+    }
+
+    let d0 = compute_d(reserve_a, reserve_b, amp)?;
+    if d0 == 0 || d1 <= d0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+
+    // Charge each side on its distance from the balance a proportional deposit
+    // would have reached, then measure growth on the fee-adjusted reserves
+    let ideal_a = mul_div(d1, reserve_a as u128, d0, false)?;
+    let ideal_b = mul_div(d1, reserve_b as u128, d0, false)?;
+    let fee_a = imbalance_fee(ideal_a, new_reserve_a, fee_rate)?;
+    let fee_b = imbalance_fee(ideal_b, new_reserve_b, fee_rate)?;
+    let adjusted_a = (new_reserve_a as u128).checked_sub(fee_a).ok_or(AMMError::InsufficientLiquidity)?;
+    let adjusted_b = (new_reserve_b as u128).checked_sub(fee_b).ok_or(AMMError::InsufficientLiquidity)?;
+    let d2 = compute_d(adjusted_a as u64, adjusted_b as u64, amp)?;
+    if d2 <= d0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+
+    let lp = mul_div(lp_supply as u128, d2 - d0, d0, false)?;
+    u64::try_from(lp).map_err(|_| AMMError::MathOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESERVE: u64 = 100_000_000;
+
+    #[test]
+    fn newton_iterations_converge_on_the_invariant() {
+        for amp in [MIN_AMP, 10, 100, 5_000, MAX_AMP] {
+            for (x, y) in [
+                (RESERVE, RESERVE),
+                (1_000, 1_000_000_000),
+                (7_777_777, 123_456_789),
+                (1_000_000_000_000_000, 1_000_000_000_000_000),
+            ] {
+                let d = compute_d(x, y, amp).unwrap();
+                // D is bounded by the constant-sum and constant-product invariants
+                let sum = x as u128 + y as u128;
+                assert!(d <= sum + 1, "amp={amp} x={x} y={y}: D={d} > {sum}");
+                assert!((d + 1) * (d + 1) >= 4 * x as u128 * y as u128, "amp={amp} x={x} y={y}: D={d}");
+                // Solving back for either side recovers the reserve; D's truncation is
+                // magnified at extreme imbalance, hence the relative slack
+                let y_back = compute_y(x as u128, d, amp).unwrap();
+                let x_back = compute_y(y as u128, d, amp).unwrap();
+                assert!(y_back.abs_diff(y as u128) <= 2 + y as u128 / 10_000_000, "amp={amp}: {y_back} vs {y}");
+                assert!(x_back.abs_diff(x as u128) <= 2 + x as u128 / 10_000_000, "amp={amp}: {x_back} vs {x}");
+            }
+        }
+        // A balanced pool's D is the sum of its reserves
+        assert!(compute_d(RESERVE, RESERVE, 100).unwrap().abs_diff(2 * RESERVE as u128) <= 1);
+        assert_eq!(compute_d(0, 0, 100).unwrap(), 0);
+        assert!(matches!(compute_d(0, RESERVE, 100), Err(AMMError::InsufficientLiquidity)));
+        assert!(matches!(compute_y(0, RESERVE as u128, 100), Err(AMMError::InsufficientLiquidity)));
+    }
+
+    #[test]
+    fn current_amp_follows_the_ramp() {
+        // Upward ramp from 100 to 1000 over [1000, 2000]
+        assert_eq!(current_amp(100, 1_000, 1_000, 2_000, 0), 100);
+        assert_eq!(current_amp(100, 1_000, 1_000, 2_000, 1_000), 100);
+        assert_eq!(current_amp(100, 1_000, 1_000, 2_000, 1_500), 550);
+        assert_eq!(current_amp(100, 1_000, 1_000, 2_000, 2_000), 1_000);
+        assert_eq!(current_amp(100, 1_000, 1_000, 2_000, 9_999), 1_000);
+        // Downward ramp
+        assert_eq!(current_amp(1_000, 100, 1_000, 2_000, 1_000), 1_000);
+        assert_eq!(current_amp(1_000, 100, 1_000, 2_000, 1_250), 775);
+        assert_eq!(current_amp(1_000, 100, 1_000, 2_000, 2_000), 100);
+        // A stopped ramp (end at or before start) holds the target
+        assert_eq!(current_amp(100, 300, 2_000, 2_000, 1_000), 300);
+    }
+
+    #[test]
+    fn amp_one_stays_close_to_constant_product() {
+        for amount_in in [1_000u64, 100_000, 1_000_000] {
+            let stable = swap_exact_in(amount_in, RESERVE, RESERVE, 0, MIN_AMP).unwrap().amount_out;
+            let constant_product = math::constant_product_amount_out(amount_in, RESERVE, RESERVE).unwrap();
+            // Flatter than x·y = k, but within 1% of it for trades up to 1% of the reserves
+            assert!(stable >= constant_product && stable <= amount_in);
+            assert!(stable - constant_product <= constant_product / 100, "{amount_in}: {stable} vs {constant_product}");
+        }
+
+        // Spot price sits between the 1:1 peg and the reserve ratio, nearer the ratio at low amp
+        let ratio = math::spot_price(RESERVE, 4 * RESERVE).unwrap();
+        let low = spot_price(RESERVE, 4 * RESERVE, MIN_AMP).unwrap();
+        let high = spot_price(RESERVE, 4 * RESERVE, 1_000).unwrap();
+        assert!(math::PRICE_SCALE < high && high < low && low < ratio, "{high} {low} {ratio}");
+        assert!(spot_price(RESERVE, RESERVE, MIN_AMP).unwrap().abs_diff(math::PRICE_SCALE) <= 1);
+    }
+
+    #[test]
+    fn spot_price_matches_small_trades() {
+        for amp in [MIN_AMP, 100, 10_000] {
+            for (reserve_in, reserve_out) in [(RESERVE, RESERVE), (RESERVE, 3 * RESERVE), (5 * RESERVE, RESERVE)] {
+                let spot = spot_price(reserve_in, reserve_out, amp).unwrap();
+                let amount_in = 1_000_000;
+                let amount_out = swap_exact_in(amount_in, reserve_in, reserve_out, 0, amp).unwrap().amount_out;
+                let execution = amount_out as u128 * math::PRICE_SCALE / amount_in as u128;
+                // A 1% trade executes just below spot
+                assert!(execution <= spot, "amp={amp}: {execution} > {spot}");
+                assert!(spot - execution <= spot / 50, "amp={amp}: {execution} vs {spot}");
+            }
+        }
+        assert!(matches!(spot_price(0, RESERVE, 100), Err(AMMError::InsufficientLiquidity)));
+    }
+
+    #[test]
+    fn exact_in_and_exact_out_round_for_the_pool() {
+        for amp in [MIN_AMP, 100, 10_000] {
+            for fee_rate in [0, 4, 30] {
+                for amount in [1u64, 999, 123_456, 10_000_000] {
+                    // Exact in: the invariant never shrinks
+                    let quote = swap_exact_in(amount, RESERVE, 2 * RESERVE, fee_rate, amp).unwrap();
+                    let after_fee = amount - quote.fee_amount;
+                    let d0 = compute_d(RESERVE, 2 * RESERVE, amp).unwrap();
+                    let d1 = compute_d(RESERVE + after_fee, 2 * RESERVE - quote.amount_out, amp).unwrap();
+                    assert!(d1 + 1 >= d0, "amp={amp} fee={fee_rate} in={amount}: {d1} < {d0}");
+
+                    // Exact out: paying the quoted input buys at least the requested output
+                    let quote = swap_exact_out(amount, RESERVE, 2 * RESERVE, fee_rate, amp).unwrap();
+                    let bought = swap_exact_in(quote.amount_in, RESERVE, 2 * RESERVE, fee_rate, amp).unwrap();
+                    assert!(bought.amount_out + 1 >= amount, "amp={amp} fee={fee_rate} out={amount}: {}", bought.amount_out);
+                    assert!(quote.amount_in > 0);
+                }
+            }
+        }
+        assert!(matches!(swap_exact_out(RESERVE, RESERVE, RESERVE, 30, 100), Err(AMMError::InsufficientLiquidity)));
+    }
+
+    #[test]
+    fn swap_round_trip_never_profits() {
+        for amp in [MIN_AMP, 100, 10_000, MAX_AMP] {
+            for fee_rate in [0, 4, 30] {
+                for (amount_in, reserve_a, reserve_b) in [
+                    (1u64, RESERVE, RESERVE),
+                    (12_345, RESERVE, 3 * RESERVE),
+                    (10_000_000, RESERVE, RESERVE),
+                    (60_000_000, 2 * RESERVE, RESERVE),
+                ] {
+                    let there = swap_exact_in(amount_in, reserve_a, reserve_b, fee_rate, amp).unwrap();
+                    let back = swap_exact_in(
+                        there.amount_out,
+                        reserve_b - there.amount_out,
+                        reserve_a + amount_in,
+                        fee_rate,
+                        amp,
+                    );
+                    let Ok(back) = back else { continue };
+                    assert!(
+                        back.amount_out <= amount_in,
+                        "amp={amp} fee={fee_rate} in={amount_in} back={}",
+                        back.amount_out
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn one_sided_deposit_then_withdraw_costs_about_a_swap_fee() {
+        let (reserve_a, reserve_b, lp_supply) = (RESERVE, RESERVE, 2 * RESERVE);
+        let amount = 1_000_000;
+        for amp in [MIN_AMP, 100, 10_000] {
+            for fee_rate in [0, 4, 30] {
+                let lp = deposit_lp_tokens(amount, 0, reserve_a, reserve_b, lp_supply, amp, fee_rate).unwrap();
+                let (new_a, new_supply) = (reserve_a + amount, lp_supply + lp);
+                let out_a = math::withdraw_amount(lp, new_a, new_supply).unwrap();
+                let out_b = math::withdraw_amount(lp, reserve_b, new_supply).unwrap();
+                // Value the B side in A with a fee-free swap against what is left
+                let out_b_in_a = swap_exact_in(out_b, reserve_b - out_b, new_a - out_a, 0, amp)
+                    .unwrap()
+                    .amount_out;
+                let value = out_a + out_b_in_a;
+                assert!(value <= amount, "amp={amp} fee={fee_rate}: {value} > {amount}");
+
+                // Half the deposit is effectively swapped into B, so it pays about that swap's fee
+                let loss = amount - value;
+                let half_swap_fee = math::swap_fee(amount / 2, fee_rate).unwrap();
+                assert!(loss + 10 >= half_swap_fee, "amp={amp} fee={fee_rate}: {loss} < {half_swap_fee}");
+                assert!(loss <= 2 * half_swap_fee + 10, "amp={amp} fee={fee_rate}: {loss}");
+            }
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! Weighted-product curve math for multi-asset pools.
+//!
+//! Invariant `V = Π B_i^w_i` with normalized weights (Σ w_i = `ONE`). Weights and
+//! ratios are 18-decimal fixed point; non-integer powers go through `ln`/`exp`
+//! and are nudged by `MAX_POW_RELATIVE_ERROR` in whichever direction favours the
+//! pool. Like `math`, the module is pure and usable off-chain for quoting.
+
+use crate::clmm_math::mul_div;
+use crate::math;
+use crate::{AMMError, MINIMUM_LIQUIDITY};
+
+/// 1.0 in fixed point; weights sum to this
+pub const ONE: u128 = 1_000_000_000_000_000_000;
+
+/// Smallest weight an asset may have (1%)
+pub const MIN_WEIGHT: u64 = 10_000_000_000_000_000;
+
+/// Share of a balance (or of the LP supply) one operation may move (30%)
+pub const MAX_RATIO: u128 = 300_000_000_000_000_000;
+
+/// Relative error bound applied to every `pow` result
+const MAX_POW_RELATIVE_ERROR: u128 = 10_000; // 1e-14
+
+/// ln(2) in fixed point
+const LN_2: i128 = 693_147_180_559_945_309;
+
+/// `a * b`, both fixed point, rounded down
+pub fn mul_down(a: u128, b: u128) -> Result<u128, AMMError> {
+    mul_div(a, b, ONE, false)
+}
+
+/// `a * b`, both fixed point, rounded up
+pub fn mul_up(a: u128, b: u128) -> Result<u128, AMMError> {
+    mul_div(a, b, ONE, true)
+}
+
+/// `a / b` as fixed point, rounded down
+pub fn div_down(a: u128, b: u128) -> Result<u128, AMMError> {
+    mul_div(a, ONE, b, false)
+}
+
+/// `a / b` as fixed point, rounded up
+pub fn div_up(a: u128, b: u128) -> Result<u128, AMMError> {
+    mul_div(a, ONE, b, true)
+}
+
+/// Natural logarithm of a positive fixed-point value
+pub fn ln(x: u128) -> Result<i128, AMMError> {
+    if x == 0 {
+        return Err(AMMError::MathOverflow);
+    }
+
+    // x = 2^k * m with m in [1, 2)
+    let mut k: i128 = 0;
+    let mut m = x;
+    while m >= 2 * ONE {
+        m /= 2;
+        k += 1;
+    }
+    while m < ONE {
+        m *= 2;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3/3 + z^5/5 + ...), z = (m - 1) / (m + 1) < 1/3
+    let z = div_down(m - ONE, m + ONE)?;
+    let z_squared = mul_down(z, z)?;
+    let mut term = z;
+    let mut sum = z;
+    let mut n = 3;
+    loop {
+        term = mul_down(term, z_squared)?;
+        if term == 0 {
+            break;
+        }
+        sum += term / n;
+        n += 2;
+    }
+
+    Ok(k * LN_2 + 2 * sum as i128)
+}
+
+/// `e^y` for a fixed-point exponent
+pub fn exp(y: i128) -> Result<u128, AMMError> {
+    // y = k * ln(2) + r with r in [0, ln(2))
+    let k = y.div_euclid(LN_2);
+    let r = y.rem_euclid(LN_2) as u128;
+
+    let mut term = ONE;
+    let mut sum = ONE;
+    let mut n = 1;
+    loop {
+        term = mul_down(term, r)? / n;
+        if term == 0 {
+            break;
+        }
+        sum += term;
+        n += 1;
+    }
+
+    if k >= 0 {
+        if k >= 128 || sum.leading_zeros() < k as u32 {
+            return Err(AMMError::MathOverflow);
+        }
+        Ok(sum << k)
+    } else if k <= -128 {
+        Ok(0)
+    } else {
+        Ok(sum >> (-k))
+    }
+}
+
+/// `base^exponent`, both fixed point
+pub fn pow(base: u128, exponent: u128) -> Result<u128, AMMError> {
+    if exponent == 0 || base == ONE {
+        return Ok(ONE);
+    }
+    if base == 0 {
+        return Ok(0);
+    }
+    let ln_base = ln(base)?;
+    let magnitude = mul_div(ln_base.unsigned_abs(), exponent, ONE, false)?;
+    let magnitude = i128::try_from(magnitude).map_err(|_| AMMError::MathOverflow)?;
+    exp(if ln_base < 0 { -magnitude } else { magnitude })
+}
+
+/// `pow` rounded up by its error bound
+pub fn pow_up(base: u128, exponent: u128) -> Result<u128, AMMError> {
+    let raw = pow(base, exponent)?;
+    let max_error = mul_up(raw, MAX_POW_RELATIVE_ERROR)? + 1;
+    raw.checked_add(max_error).ok_or(AMMError::MathOverflow)
+}
+
+/// `pow` rounded down by its error bound
+pub fn pow_down(base: u128, exponent: u128) -> Result<u128, AMMError> {
+    let raw = pow(base, exponent)?;
+    let max_error = mul_up(raw, MAX_POW_RELATIVE_ERROR)? + 1;
+    Ok(raw.saturating_sub(max_error))
+}
+
+/// Weights must be at least `MIN_WEIGHT` each and sum to `ONE`
+pub fn validate_weights(weights: &[u64]) -> Result<(), AMMError> {
+    let total: u128 = weights.iter().map(|w| *w as u128).sum();
+    if total != ONE || weights.iter().any(|w| *w < MIN_WEIGHT) {
+        return Err(AMMError::InvalidWeights);
+    }
+    Ok(())
+}
+
+/// Exact-input swap between two constituents: fee first, then
+/// `out = B_o * (1 - (B_i / (B_i + A_i))^(w_i / w_o))`, rounded down
+pub fn swap_exact_in(
+    amount_in: u64,
+    balance_in: u64,
+    weight_in: u64,
+    balance_out: u64,
+    weight_out: u64,
+    fee_rate: u64,
+) -> Result<math::SwapQuote, AMMError> {
+    if balance_in == 0 || balance_out == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    let fee_amount = math::swap_fee(amount_in, fee_rate)?;
+    let amount_in_after_fee = amount_in - fee_amount;
+    if amount_in_after_fee as u128 > mul_down(balance_in as u128, MAX_RATIO)? {
+        return Err(AMMError::WeightedRatioExceeded);
+    }
+
+    let base = div_up(balance_in as u128, balance_in as u128 + amount_in_after_fee as u128)?;
+    let exponent = div_down(weight_in as u128, weight_out as u128)?;
+    let power = pow_up(base, exponent)?;
+    let amount_out = mul_down(balance_out as u128, ONE.saturating_sub(power))?;
+
+    Ok(math::SwapQuote {
+        fee_amount,
+        amount_out: u64::try_from(amount_out).map_err(|_| AMMError::MathOverflow)?,
+    })
+}
+
+/// LP tokens for the first join: the invariant `Π B_i^w_i` minus the locked `MINIMUM_LIQUIDITY`
+pub fn initial_lp_tokens(amounts: &[u64], weights: &[u64]) -> Result<u64, AMMError> {
+    let mut invariant = ONE;
+    for (amount, weight) in amounts.iter().zip(weights) {
+        if *amount == 0 {
+            return Err(AMMError::InsufficientLiquidity);
+        }
+        let factor = pow_down(*amount as u128 * ONE, *weight as u128)?;
+        invariant = mul_down(invariant, factor)?;
+    }
+    let liquidity = u64::try_from(invariant / ONE).map_err(|_| AMMError::MathOverflow)?;
+    liquidity
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .filter(|lp| *lp > 0)
+        .ok_or(AMMError::InsufficientLiquidity)
+}
+
+/// LP tokens for a single-asset join. Only the part of the deposit a proportional
+/// join would not have needed, `amount * (1 - w)`, pays the swap fee.
+pub fn single_asset_join_lp_tokens(
+    amount_in: u64,
+    balance: u64,
+    weight: u64,
+    lp_supply: u64,
+    fee_rate: u64,
+) -> Result<u64, AMMError> {
+    if balance == 0 || lp_supply == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    if amount_in as u128 > mul_down(balance as u128, MAX_RATIO)? {
+        return Err(AMMError::WeightedRatioExceeded);
+    }
+
+    let taxable = mul_up(amount_in as u128, ONE - weight as u128)? as u64;
+    let amount_in_after_fee = amount_in - math::swap_fee(taxable, fee_rate)?;
+    let ratio = div_down(balance as u128 + amount_in_after_fee as u128, balance as u128)?;
+    let power = pow_down(ratio, weight as u128)?;
+    let lp = mul_down(lp_supply as u128, power.saturating_sub(ONE))?;
+    u64::try_from(lp).map_err(|_| AMMError::MathOverflow)
+}
+
+/// Tokens out for burning `lp_amount` into a single asset, after the fee on the
+/// non-proportional part, rounded down
+pub fn single_asset_exit_amount(
+    lp_amount: u64,
+    balance: u64,
+    weight: u64,
+    lp_supply: u64,
+    fee_rate: u64,
+) -> Result<u64, AMMError> {
+    if lp_supply == 0 {
+        return Err(AMMError::InsufficientLiquidity);
+    }
+    if lp_amount as u128 > mul_down(lp_supply as u128, MAX_RATIO)? {
+        return Err(AMMError::WeightedRatioExceeded);
+    }
+
+    let ratio = div_up((lp_supply - lp_amount) as u128, lp_supply as u128)?;
+    let exponent = div_down(ONE, weight as u128)?;
+    let power = pow_up(ratio, exponent)?;
+    let amount_before_fee = mul_down(balance as u128, ONE.saturating_sub(power))?;
+    let amount_before_fee = u64::try_from(amount_before_fee).map_err(|_| AMMError::MathOverflow)?;
+
+    let taxable = mul_up(amount_before_fee as u128, ONE - weight as u128)? as u64;
+    Ok(amount_before_fee - math::swap_fee(taxable, fee_rate)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF: u64 = (ONE / 2) as u64;
+    const EIGHTY: u64 = 800_000_000_000_000_000;
+    const TWENTY: u64 = 200_000_000_000_000_000;
+
+    fn to_fixed(x: f64) -> u128 {
+        (x * ONE as f64) as u128
+    }
+
+    #[test]
+    fn pow_bounds_bracket_the_exact_result() {
+        // Exact results: 4^0.5 = 2, 0.25^0.5 = 0.5, 2^3 = 8
+        for (base, exponent, exact) in [
+            (4 * ONE, ONE / 2, 2 * ONE),
+            (ONE / 4, ONE / 2, ONE / 2),
+            (2 * ONE, 3 * ONE, 8 * ONE),
+        ] {
+            let down = pow_down(base, exponent).unwrap();
+            let up = pow_up(base, exponent).unwrap();
+            assert!(down <= exact && exact <= up, "{base}^{exponent}: {down} {exact} {up}");
+        }
+
+        // Against f64 across the ranges swaps, joins and exits use
+        for (base, exponent) in [(0.7, 0.25), (0.95, 4.0), (1.3, 0.2), (1.0001, 0.8), (0.5, 99.0)] {
+            let (base_fixed, exponent_fixed) = (to_fixed(base), to_fixed(exponent));
+            let expected = to_fixed(f64::powf(base, exponent));
+            let down = pow_down(base_fixed, exponent_fixed).unwrap();
+            let up = pow_up(base_fixed, exponent_fixed).unwrap();
+            // f64 itself is only good to ~1e-16, well inside the 1e-14 bound
+            let slack = expected / 1_000_000_000_000_000;
+            assert!(down <= expected + slack, "{base}^{exponent}: {down} > {expected}");
+            assert!(up + slack >= expected, "{base}^{exponent}: {up} < {expected}");
+            // The bounds stay within a few times the advertised relative error
+            assert!(up - down <= mul_up(expected, 4 * MAX_POW_RELATIVE_ERROR).unwrap() + 2);
+        }
+
+        assert_eq!(pow_up(ONE, 7 * ONE).unwrap(), ONE + mul_up(ONE, MAX_POW_RELATIVE_ERROR).unwrap() + 1);
+        assert_eq!(pow_down(0, ONE).unwrap(), 0);
+        assert_eq!(pow(5 * ONE, 0).unwrap(), ONE);
+    }
+
+    #[test]
+    fn validate_weights_rejects_bad_sums_and_tiny_weights() {
+        assert!(validate_weights(&[HALF, HALF]).is_ok());
+        assert!(validate_weights(&[EIGHTY, TWENTY]).is_ok());
+        assert!(matches!(validate_weights(&[HALF, HALF - 1]), Err(AMMError::InvalidWeights)));
+        assert!(matches!(
+            validate_weights(&[ONE as u64 - MIN_WEIGHT + 1, MIN_WEIGHT - 1]),
+            Err(AMMError::InvalidWeights)
+        ));
+    }
+
+    #[test]
+    fn max_ratio_is_enforced() {
+        let balance = 1_000_000_000u64;
+        let at_limit = mul_down(balance as u128, MAX_RATIO).unwrap() as u64;
+
+        assert!(swap_exact_in(at_limit, balance, HALF, balance, HALF, 0).is_ok());
+        assert!(matches!(
+            swap_exact_in(at_limit + 1, balance, HALF, balance, HALF, 0),
+            Err(AMMError::WeightedRatioExceeded)
+        ));
+        // The limit applies to the input after the fee
+        assert!(swap_exact_in(at_limit + 1, balance, HALF, balance, HALF, 30).is_ok());
+
+        assert!(single_asset_join_lp_tokens(at_limit, balance, HALF, balance, 0).is_ok());
+        assert!(matches!(
+            single_asset_join_lp_tokens(at_limit + 1, balance, HALF, balance, 0),
+            Err(AMMError::WeightedRatioExceeded)
+        ));
+
+        assert!(single_asset_exit_amount(at_limit, balance, HALF, balance, 0).is_ok());
+        assert!(matches!(
+            single_asset_exit_amount(at_limit + 1, balance, HALF, balance, 0),
+            Err(AMMError::WeightedRatioExceeded)
+        ));
+    }
+
+    #[test]
+    fn swap_matches_constant_product_for_equal_weights() {
+        let quote = swap_exact_in(1_000_000, 100_000_000, HALF, 100_000_000, HALF, 0).unwrap();
+        let constant_product = math::constant_product_amount_out(1_000_000, 100_000_000, 100_000_000).unwrap();
+        assert!(quote.amount_out <= constant_product);
+        assert!(constant_product - quote.amount_out <= 1);
+    }
+
+    #[test]
+    fn single_asset_join_then_exit_never_profits() {
+        for weight in [HALF, EIGHTY, TWENTY, MIN_WEIGHT] {
+            for fee_rate in [0, 30, 100] {
+                for (amount_in, balance, lp_supply) in [
+                    (1u64, 1_000_000u64, 1_000_000u64),
+                    (12_345, 1_000_000, 3_000_000),
+                    (250_000, 1_000_000, 1_000_000),
+                    (299_999_999, 1_000_000_000, 7_000_000_000),
+                    (1_000_000_000, 1_000_000_000_000, 500_000),
+                ] {
+                    let lp = single_asset_join_lp_tokens(amount_in, balance, weight, lp_supply, fee_rate).unwrap();
+                    if lp == 0 {
+                        continue;
+                    }
+                    let amount_out =
+                        single_asset_exit_amount(lp, balance + amount_in, weight, lp_supply + lp, fee_rate).unwrap();
+                    assert!(
+                        amount_out <= amount_in,
+                        "w={weight} fee={fee_rate} in={amount_in} out={amount_out}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn initial_lp_tokens_is_the_weighted_geometric_mean() {
+        // sqrt(4e6 * 1e6) = 2e6
+        let lp = initial_lp_tokens(&[4_000_000, 1_000_000], &[HALF, HALF]).unwrap();
+        let expected = 2_000_000 - MINIMUM_LIQUIDITY;
+        assert!(lp <= expected && expected - lp <= 1);
+        assert!(matches!(initial_lp_tokens(&[0, 1_000_000], &[HALF, HALF]), Err(AMMError::InsufficientLiquidity)));
+    }
+}
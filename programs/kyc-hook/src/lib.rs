@@ -84,28 +84,7 @@ pub mod kyc_hook {
     ) -> Result<()> {
         msg!("🚀 Initializing ExtraAccountMetaList for mint: {}", ctx.accounts.mint.key());
         
-        // Define the extra accounts needed for our KYC validation
-        let account_metas = vec![
-            // KYC System account (global config)
-            ExtraAccountMeta::new_with_seeds(
-                &[Seed::Literal {
-                    bytes: b"kyc_system".to_vec(),
-                }],
-                false, // is_signer
-                true,  // is_writable (to update stats)
-            )?,
-            // KYC record for the owner/authority performing the transfer
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"kyc_record".to_vec(),
-                    },
-                    Seed::AccountKey { index: 3 }, // Index 3 is the owner in transfer hook context
-                ],
-                false, // is_signer
-                false, // is_writable (read-only for transfer validation)
-            )?,
-        ];
+        let account_metas = extra_account_metas()?;
 
         // Calculate account size needed
         let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
@@ -120,6 +99,45 @@ pub mod kyc_hook {
         Ok(())
     }
 
+    /// Rewrite an existing ExtraAccountMetaList with the current extra accounts.
+    ///
+    /// Lists created before the pause state existed lack its entries, so transfers
+    /// of their mint ignore a pause until this runs. The contents are fixed by the
+    /// program, so anyone may pay for the update.
+    pub fn update_extra_account_meta_list(
+        ctx: Context<UpdateExtraAccountMetaList>,
+    ) -> Result<()> {
+        let account_metas = extra_account_metas()?;
+        let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
+        let meta_list = ctx.accounts.extra_account_meta_list.to_account_info();
+        
+        if meta_list.data_len() < account_size {
+            let rent_due = Rent::get()?
+                .minimum_balance(account_size)
+                .saturating_sub(meta_list.lamports());
+            if rent_due > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.payer.to_account_info(),
+                            to: meta_list.clone(),
+                        },
+                    ),
+                    rent_due,
+                )?;
+            }
+            meta_list.realloc(account_size, false)?;
+        }
+        
+        let mut data = meta_list.try_borrow_mut_data()?;
+        ExtraAccountMetaList::update::<ExecuteInstruction>(&mut data, &account_metas)?;
+        
+        msg!("🔄 ExtraAccountMetaList for mint {} updated with {} extra accounts",
+             ctx.accounts.mint.key(), account_metas.len());
+        Ok(())
+    }
+
     /// ✅ NEW: Transfer Hook Execute - REAL implementation called by Token-2022
     pub fn transfer_hook_execute(
         ctx: Context<TransferHookExecute>,
@@ -131,6 +149,14 @@ pub mod kyc_hook {
         msg!("📦 Source: {}", ctx.accounts.source_token.key());
         msg!("📦 Destination: {}", ctx.accounts.destination_token.key());
 
+        // Reject everything while the issuer has paused the token
+        if let Some(pause_state) = &ctx.accounts.pause_state {
+            if token_layer_pause::is_transfer_paused(pause_state, &ctx.accounts.mint.key())? {
+                msg!("❌ Transfer BLOCKED: Token {} is paused", ctx.accounts.mint.key());
+                return Err(KYCError::TokenPaused.into());
+            }
+        }
+
         // Load KYC record for the owner
         let kyc_record = &ctx.accounts.kyc_record;
         let kyc_system = &mut ctx.accounts.kyc_system;
@@ -225,6 +251,14 @@ pub mod kyc_hook {
                 msg!("👤 Owner: {}", owner.key());
                 msg!("💰 Amount: {}", amount);
 
+                // Pause state is only present for meta lists created or updated with it
+                if let Some(pause_state) = accounts.get(8) {
+                    if token_layer_pause::is_transfer_paused(pause_state, mint.key)? {
+                        msg!("❌ Token {} is paused - Transfer BLOCKED", mint.key());
+                        return Err(KYCError::TokenPaused.into());
+                    }
+                }
+
                 // Load and validate KYC record
                 let kyc_data = kyc_record.try_borrow_data()
                     .map_err(|_| KYCError::InvalidKYCRecord)?;
@@ -303,6 +337,45 @@ pub mod kyc_hook {
     }
 }
 
+/// Extra accounts the KYC hook needs on every transfer
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![
+        // KYC System account (global config)
+        ExtraAccountMeta::new_with_seeds(
+            &[Seed::Literal {
+                bytes: b"kyc_system".to_vec(),
+            }],
+            false, // is_signer
+            true,  // is_writable (to update stats)
+        )?,
+        // KYC record for the owner/authority performing the transfer
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"kyc_record".to_vec(),
+                },
+                Seed::AccountKey { index: 3 }, // Index 3 is the owner in transfer hook context
+            ],
+            false, // is_signer
+            false, // is_writable (read-only for transfer validation)
+        )?,
+        // Token layer program that owns the per-mint pause state
+        ExtraAccountMeta::new_with_pubkey(&token_layer_pause::ID, false, false)?,
+        // Pause state PDA for this mint, derived from the token layer program (index 7)
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            7,
+            &[
+                Seed::Literal {
+                    bytes: token_layer_pause::PAUSE_STATE_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // Index 1 is the mint
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+    ])
+}
+
 // ========== ACCOUNT STRUCTURES ==========
 
 /// Initialize KYC System
@@ -380,6 +453,26 @@ pub struct InitializeExtraAccountMetaList<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: ExtraAccountMetaList Account, rewritten in place
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    
+    /// CHECK: The mint that this hook is for
+    pub mint: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
 /// ✅ NEW: Transfer Hook Execute - Called by Token-2022 during transfers
 #[derive(Accounts)]
 pub struct TransferHookExecute<'info> {
@@ -408,6 +501,13 @@ pub struct TransferHookExecute<'info> {
         bump
     )]
     pub kyc_record: Account<'info, KYCRecord>,
+    
+    /// CHECK: Token layer program
+    #[account(address = token_layer_pause::ID)]
+    pub token_layer_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token layer pause state for the mint, validated by `is_transfer_paused`
+    pub pause_state: Option<UncheckedAccount<'info>>,
 }
 
 /// Get KYC Statistics
@@ -463,4 +563,6 @@ pub enum KYCError {
     InvalidKYCLevel,
     #[msg("KYC record not found")]
     KYCRecordNotFound,
+    #[msg("Token transfers are paused")]
+    TokenPaused,
 }
\ No newline at end of file
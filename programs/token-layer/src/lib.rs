@@ -1,22 +1,40 @@
 // hookswap_amm/programs/token-layer/src/lib.rs - FIXED VERSION
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 use anchor_lang::solana_program::{
-    program::invoke,
+    program::{invoke, invoke_signed},
+    program_option::COption,
     program_pack::Pack,
+    system_instruction,
 };
 use anchor_spl::token_2022::Token2022;
 use anchor_spl::token_interface::{Mint, TokenAccount};
+use kyc_hook::KYCRecord;
+use whitelist_hook::Whitelist;
 use spl_token_2022::{
-    instruction::{initialize_mint2, mint_to},
+    instruction::{
+        initialize_mint2, mint_to, reallocate, set_authority, transfer_checked,
+        AuthorityType, TokenInstruction,
+    },
     extension::{
-        transfer_hook::{TransferHook, instruction::initialize as initialize_transfer_hook},
+        memo_transfer::instruction::enable_required_transfer_memos,
+        transfer_hook::{
+            TransferHook,
+            instruction::{initialize as initialize_transfer_hook, update as update_transfer_hook},
+        },
+        confidential_transfer::instruction::{
+            approve_account as approve_confidential_account_ix,
+            ConfidentialTransferInstruction,
+        },
         ExtensionType,
         StateWithExtensions,
         BaseStateWithExtensions,
     },
 };
 
-declare_id!("HJ4MosN8hG5qd6WFMKQcBmYVhHuX1EKdPZ1LyaPSdYLA");
+// The program id is declared next to `PauseState`, which hooks read as owned by it
+pub use token_layer_pause::{check_id, id, ID};
+pub use token_layer_pause::{is_transfer_paused, PauseState, PAUSE_STATE_SEED};
 
 #[program]
 pub mod token_layer {
@@ -27,6 +45,123 @@ pub mod token_layer {
         Ok(())
     }
 
+    /// Initialize global token layer config (creation fee + treasury). Only the
+    /// program's upgrade authority may create it, and becomes its admin.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        treasury: Pubkey,
+        creation_fee: u64,
+        fee_mint: Option<Pubkey>,
+    ) -> Result<()> {
+        let program_data = ProgramData::try_deserialize(
+            &mut &ctx.accounts.program_data.try_borrow_data()?[..]
+        )?;
+        require!(
+            program_data.upgrade_authority_address == Some(ctx.accounts.admin.key()),
+            TokenLayerError::Unauthorized
+        );
+        
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.treasury = treasury;
+        config.creation_fee = creation_fee;
+        config.fee_mint = fee_mint;
+        config.fee_waivers = Vec::new();
+        config.timelock = None;
+        config.created_at = Clock::get()?.unix_timestamp;
+        config.bump = ctx.bumps.config;
+        
+        msg!("🏗️ Token Layer config initialized");
+        msg!("🏦 Treasury: {}", treasury);
+        msg!("💸 Creation fee: {} ({})", creation_fee,
+             fee_mint.map_or("SOL".to_string(), |m| m.to_string()));
+        Ok(())
+    }
+
+    /// Update the token creation fee (in lamports, or in `fee_mint` base units)
+    pub fn set_creation_fee(
+        ctx: Context<UpdateConfig>,
+        creation_fee: u64,
+        fee_mint: Option<Pubkey>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.timelock.is_none(), TokenLayerError::TimelockRequired);
+        config.creation_fee = creation_fee;
+        config.fee_mint = fee_mint;
+        
+        msg!("💸 Creation fee updated: {} ({})", creation_fee,
+             fee_mint.map_or("SOL".to_string(), |m| m.to_string()));
+        
+        emit!(CreationFeeUpdatedEvent {
+            creation_fee,
+            fee_mint,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Update the treasury receiving creation fees
+    pub fn set_treasury(
+        ctx: Context<UpdateConfig>,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.timelock.is_none(), TokenLayerError::TimelockRequired);
+        let old_treasury = config.treasury;
+        config.treasury = treasury;
+        
+        msg!("🏦 Treasury updated: {} -> {}", old_treasury, treasury);
+        
+        emit!(TreasuryUpdatedEvent {
+            old_treasury,
+            new_treasury: treasury,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Waive the creation fee for a partner creator
+    pub fn add_fee_waiver(
+        ctx: Context<UpdateConfig>,
+        creator: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.timelock.is_none(), TokenLayerError::TimelockRequired);
+        config.add_fee_waiver(creator)?;
+        
+        msg!("🤝 Fee waiver added for creator: {} (total: {})", 
+             creator, config.fee_waivers.len());
+        
+        emit!(FeeWaiverAddedEvent {
+            creator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Remove a creator from the fee waiver list
+    pub fn remove_fee_waiver(
+        ctx: Context<UpdateConfig>,
+        creator: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.timelock.is_none(), TokenLayerError::TimelockRequired);
+        config.remove_fee_waiver(creator)?;
+        
+        msg!("❌ Fee waiver removed for creator: {} (remaining: {})", 
+             creator, config.fee_waivers.len());
+        
+        emit!(FeeWaiverRemovedEvent {
+            creator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
     /// Create REAL Token-2022 with Transfer Hook Extension - FIXED
     pub fn create_token_2022_with_hooks(
         ctx: Context<CreateToken2022WithHooks>,
@@ -62,6 +197,22 @@ pub mod token_layer {
             return Err(TokenLayerError::InvalidHookProgram.into());
         }
         
+        // ✅ Charge creation fee (waived for partner creators)
+        charge_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            CreationFeeAccounts {
+                treasury: ctx.accounts.treasury.as_ref(),
+                fee_source: ctx.accounts.fee_source.as_ref(),
+                treasury_token_account: ctx.accounts.treasury_token_account.as_ref(),
+                fee_mint: ctx.accounts.fee_mint.as_ref(),
+                fee_token_program: ctx.accounts.fee_token_program.as_ref(),
+            },
+        )?;
+        
         // 1. Calculate space needed for mint with Transfer Hook extension
         let transfer_hook_extension = ExtensionType::TransferHook;
         let mint_space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[transfer_hook_extension])
@@ -133,6 +284,7 @@ pub mod token_layer {
         token_info.total_supply = initial_supply;
         token_info.has_transfer_hooks = true;
         token_info.token_program_id = spl_token_2022::id();
+        token_info.has_confidential_transfers = false;
 
         msg!("✅ REAL Token-2022 created successfully!");
         msg!("🪙 Mint: {}", ctx.accounts.mint.key());
@@ -180,6 +332,22 @@ pub mod token_layer {
             TokenLayerError::InvalidSupply
         );
         
+        // ✅ Charge creation fee (waived for partner creators)
+        charge_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            CreationFeeAccounts {
+                treasury: ctx.accounts.treasury.as_ref(),
+                fee_source: ctx.accounts.fee_source.as_ref(),
+                treasury_token_account: ctx.accounts.treasury_token_account.as_ref(),
+                fee_mint: ctx.accounts.fee_mint.as_ref(),
+                fee_token_program: ctx.accounts.fee_token_program.as_ref(),
+            },
+        )?;
+        
         // 1. Calculate space needed for basic mint
         let mint_space = spl_token_2022::state::Mint::LEN;
         
@@ -231,6 +399,7 @@ pub mod token_layer {
         token_info.total_supply = initial_supply;
         token_info.has_transfer_hooks = false;
         token_info.token_program_id = spl_token_2022::id();
+        token_info.has_confidential_transfers = false;
 
         msg!("✅ Basic Token-2022 created successfully!");
         
@@ -247,6 +416,173 @@ pub mod token_layer {
         Ok(())
     }
 
+    /// Create Token-2022 with ConfidentialTransferMint extension.
+    ///
+    /// The mint never gets a TransferHook extension: confidential transfers don't
+    /// invoke the hook program, so a hook on a confidential mint could be bypassed.
+    /// Supply is minted afterwards with `mint_tokens`.
+    pub fn create_confidential_token_2022(
+        ctx: Context<CreateConfidentialToken2022>,
+        name: String,
+        symbol: String,
+        decimals: u8,
+        config: ConfidentialTransferConfig,
+    ) -> Result<()> {
+        msg!("🕶️ Creating confidential Token-2022: {} ({})", name, symbol);
+        
+        // ✅ VALIDATION CHECKS
+        require!(
+            name.len() <= 50 && !name.is_empty(),
+            TokenLayerError::InvalidTokenName
+        );
+        require!(
+            symbol.len() <= 10 && !symbol.is_empty(),
+            TokenLayerError::InvalidTokenSymbol
+        );
+        require!(
+            decimals <= 9,
+            TokenLayerError::InvalidDecimals
+        );
+        
+        // ✅ Charge creation fee (waived for partner creators)
+        charge_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            CreationFeeAccounts {
+                treasury: ctx.accounts.treasury.as_ref(),
+                fee_source: ctx.accounts.fee_source.as_ref(),
+                treasury_token_account: ctx.accounts.treasury_token_account.as_ref(),
+                fee_mint: ctx.accounts.fee_mint.as_ref(),
+                fee_token_program: ctx.accounts.fee_token_program.as_ref(),
+            },
+        )?;
+        
+        // 1. Calculate space needed for mint with ConfidentialTransferMint extension
+        let mint_space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+            &[ExtensionType::ConfidentialTransferMint],
+        ).map_err(|_| TokenLayerError::ExtensionInitializationFailed)?;
+        
+        msg!("📏 Mint space needed: {} bytes (with extensions)", mint_space);
+
+        // 2. Create mint account with proper space
+        let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.mint.key(),
+            ctx.accounts.rent.minimum_balance(mint_space),
+            mint_space as u64,
+            &spl_token_2022::id(),
+        );
+
+        invoke(
+            &create_account_ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        ).map_err(|_| TokenLayerError::TokenCreationFailed)?;
+
+        // 3. Initialize ConfidentialTransferMint extension FIRST
+        let init_confidential_ix = initialize_confidential_transfer_mint(
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.authority.key(),
+            &config,
+        );
+
+        invoke(
+            &init_confidential_ix,
+            &[
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.token_2022_program.to_account_info(),
+            ],
+        ).map_err(|_| TokenLayerError::ConfidentialTransferSetupFailed)?;
+
+        // 4. Initialize the mint
+        let init_mint_ix = initialize_mint2(
+            &spl_token_2022::id(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.authority.key(),
+            Some(&ctx.accounts.authority.key()),
+            decimals,
+        ).map_err(|_| TokenLayerError::TokenCreationFailed)?;
+
+        invoke(
+            &init_mint_ix,
+            &[
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+                ctx.accounts.token_2022_program.to_account_info(),
+            ],
+        ).map_err(|_| TokenLayerError::TokenCreationFailed)?;
+
+        // 5. Store token metadata
+        let token_info = &mut ctx.accounts.token_info;
+        token_info.name = name.clone();
+        token_info.symbol = symbol.clone();
+        token_info.decimals = decimals;
+        token_info.mint = ctx.accounts.mint.key();
+        token_info.hook_program_id = None;
+        token_info.created_at = Clock::get()?.unix_timestamp;
+        token_info.creator = ctx.accounts.authority.key();
+        token_info.total_supply = 0;
+        token_info.has_transfer_hooks = false;
+        token_info.token_program_id = spl_token_2022::id();
+        token_info.has_confidential_transfers = true;
+
+        msg!("✅ Confidential Token-2022 created successfully!");
+        msg!("🪙 Mint: {}", ctx.accounts.mint.key());
+        msg!("🔓 Auto-approve new accounts: {}", config.auto_approve_new_accounts);
+        msg!("👁️  Auditor configured: {}", config.auditor_elgamal_pubkey.is_some());
+        
+        // ✅ Emit event
+        emit!(TokenCreatedEvent {
+            mint: ctx.accounts.mint.key(),
+            name,
+            symbol,
+            creator: ctx.accounts.authority.key(),
+            hook_program_id: None,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Approve a token account for confidential transfers
+    pub fn approve_confidential_account(
+        ctx: Context<ApproveConfidentialAccount>,
+    ) -> Result<()> {
+        msg!("🕶️ Approving confidential account: {}", ctx.accounts.token_account.key());
+        
+        require!(
+            ctx.accounts.token_info.has_confidential_transfers,
+            TokenLayerError::ConfidentialTransfersNotEnabled
+        );
+        
+        let approve_ix = approve_confidential_account_ix(
+            &spl_token_2022::id(),
+            &ctx.accounts.token_account.key(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.authority.key(),
+            &[],
+        ).map_err(|_| TokenLayerError::ConfidentialTransferSetupFailed)?;
+
+        invoke(
+            &approve_ix,
+            &[
+                ctx.accounts.token_account.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.token_2022_program.to_account_info(),
+            ],
+        ).map_err(|_| TokenLayerError::ConfidentialTransferSetupFailed)?;
+
+        msg!("✅ Account approved for confidential transfers");
+        Ok(())
+    }
+
     /// Create associated token account for Token-2022
     pub fn create_associated_token_account(
         ctx: Context<CreateAssociatedTokenAccount>,
@@ -276,7 +612,125 @@ pub mod token_layer {
         Ok(())
     }
 
-    /// Mint tokens to an account (with hook validation)
+    /// Create an associated token account only for wallets that pass the mint's
+    /// compliance hook, optionally enabling account-level extensions
+    pub fn create_compliant_associated_token_account(
+        ctx: Context<CreateCompliantAssociatedTokenAccount>,
+        options: AccountExtensionOptions,
+    ) -> Result<()> {
+        let wallet = ctx.accounts.wallet.key();
+        msg!("🛡️ Creating compliant ATA for wallet {} (mint: {})", wallet, ctx.accounts.mint.key());
+        
+        // 1. Pre-check the wallet against the mint's hook so onboarding fails early
+        match ctx.accounts.token_info.hook_program_id {
+            Some(hook) if hook == kyc_hook::ID => {
+                let record = ctx.accounts.compliance_account.as_ref()
+                    .ok_or(TokenLayerError::MissingComplianceAccount)?;
+                check_kyc_compliance(record, &wallet)?;
+            }
+            Some(hook) if hook == whitelist_hook::ID => {
+                let whitelist = ctx.accounts.compliance_account.as_ref()
+                    .ok_or(TokenLayerError::MissingComplianceAccount)?;
+                check_whitelist_compliance(whitelist, &wallet)?;
+            }
+            Some(hook) => {
+                msg!("ℹ️  Hook {} has no onboarding pre-check", hook);
+            }
+            None => {
+                msg!("ℹ️  No transfer hook - compliance pre-check skipped");
+            }
+        }
+        
+        // 2. Create the ATA
+        let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account(
+            &ctx.accounts.payer.key(),
+            &wallet,
+            &ctx.accounts.mint.key(),
+            &spl_token_2022::id(),
+        );
+
+        invoke(
+            &create_ata_ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.associated_token.to_account_info(),
+                ctx.accounts.wallet.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_2022_program.to_account_info(),
+                ctx.accounts.associated_token_program.to_account_info(),
+            ],
+        ).map_err(|_| TokenLayerError::TokenCreationFailed)?;
+        
+        // 3. Optional account-level extensions (owner must sign)
+        let mut extension_types = Vec::new();
+        if options.require_memo_transfers {
+            extension_types.push(ExtensionType::MemoTransfer);
+        }
+        if options.reserve_cpi_guard {
+            extension_types.push(ExtensionType::CpiGuard);
+        }
+        
+        if !extension_types.is_empty() {
+            require!(
+                ctx.accounts.wallet.is_signer,
+                TokenLayerError::WalletSignatureRequired
+            );
+            
+            let reallocate_ix = reallocate(
+                &spl_token_2022::id(),
+                &ctx.accounts.associated_token.key(),
+                &ctx.accounts.payer.key(),
+                &wallet,
+                &[],
+                &extension_types,
+            ).map_err(|_| TokenLayerError::ExtensionInitializationFailed)?;
+
+            invoke(
+                &reallocate_ix,
+                &[
+                    ctx.accounts.associated_token.to_account_info(),
+                    ctx.accounts.payer.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.wallet.to_account_info(),
+                    ctx.accounts.token_2022_program.to_account_info(),
+                ],
+            ).map_err(|_| TokenLayerError::ExtensionInitializationFailed)?;
+            
+            if options.require_memo_transfers {
+                let memo_ix = enable_required_transfer_memos(
+                    &spl_token_2022::id(),
+                    &ctx.accounts.associated_token.key(),
+                    &wallet,
+                    &[],
+                ).map_err(|_| TokenLayerError::ExtensionInitializationFailed)?;
+
+                invoke(
+                    &memo_ix,
+                    &[
+                        ctx.accounts.associated_token.to_account_info(),
+                        ctx.accounts.wallet.to_account_info(),
+                        ctx.accounts.token_2022_program.to_account_info(),
+                    ],
+                ).map_err(|_| TokenLayerError::ExtensionInitializationFailed)?;
+                
+                msg!("📝 Required transfer memos enabled");
+            }
+            
+            if options.reserve_cpi_guard {
+                // Token-2022 rejects CpiGuard toggles inside a CPI, so only the
+                // space is reserved here; the wallet enables it top-level
+                msg!("🛡️ CpiGuard space reserved - enable with a top-level EnableCpiGuard");
+            }
+        }
+
+        msg!("✅ Compliant Associated Token Account created: {}", ctx.accounts.associated_token.key());
+        Ok(())
+    }
+
+    /// Mint tokens to an account (with hook validation). Once the token has a
+    /// timelock the mint authority is its PDA, and minting goes through a
+    /// `MintTokens` timelock action instead.
     pub fn mint_tokens(
         ctx: Context<MintTokens>,
         amount: u64,
@@ -343,40 +797,825 @@ pub mod token_layer {
         Ok(false)
     }
 
-    /// Get comprehensive token information
-    pub fn get_token_info(ctx: Context<GetTokenInfo>) -> Result<()> {
-        let token_info = &ctx.accounts.token_info;
+    /// Initialize the per-mint pause state consulted by transfer hooks
+    pub fn initialize_pause_state(
+        ctx: Context<InitializePauseState>,
+        pause_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.token_info.creator == ctx.accounts.authority.key(),
+            TokenLayerError::Unauthorized
+        );
         
-        msg!("📋 Token Information:");
-        msg!("🏷️  Name: {}", token_info.name);
-        msg!("🔤 Symbol: {}", token_info.symbol);
-        msg!("🔢 Decimals: {}", token_info.decimals);
-        msg!("🪙 Mint: {}", token_info.mint);
-        msg!("🔗 Hook Program: {:?}", token_info.hook_program_id);
-        msg!("👤 Creator: {}", token_info.creator);
-        msg!("📊 Supply: {}", token_info.total_supply);
-        msg!("🔒 Has Hooks: {}", token_info.has_transfer_hooks);
-        msg!("⚙️  Token Program: {}", token_info.token_program_id);
+        let pause_state = &mut ctx.accounts.pause_state;
+        pause_state.mint = ctx.accounts.token_info.mint;
+        pause_state.pause_authority = pause_authority;
+        pause_state.is_paused = false;
+        pause_state.last_reason = String::new();
+        pause_state.updated_at = Clock::get()?.unix_timestamp;
+        pause_state.bump = ctx.bumps.pause_state;
         
+        msg!("⏸️  Pause state initialized for mint: {}", pause_state.mint);
+        msg!("👤 Pause authority: {}", pause_authority);
         Ok(())
     }
-}
 
-// ========== ACCOUNT STRUCTURES - FIXED ==========
+    /// Pause all transfers of a token (enforced by its transfer hook). Hooked mints
+    /// whose meta list predates the pause state need the hook's
+    /// `update_extra_account_meta_list` first, or the hook never sees the pause.
+    pub fn pause_token(
+        ctx: Context<SetPauseState>,
+        reason: String,
+    ) -> Result<()> {
+        require!(reason.len() <= 100, TokenLayerError::InvalidPauseReason);
+        
+        let pause_state = &mut ctx.accounts.pause_state;
+        require!(!pause_state.is_paused, TokenLayerError::AlreadyPaused);
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        pause_state.is_paused = true;
+        pause_state.last_reason = reason.clone();
+        pause_state.updated_at = timestamp;
+        
+        msg!("⏸️  Transfers PAUSED for mint: {} ({})", pause_state.mint, reason);
+        
+        emit!(TokenPausedEvent {
+            mint: pause_state.mint,
+            authority: ctx.accounts.pause_authority.key(),
+            reason,
+            timestamp,
+        });
+        
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct Initialize {}
+    /// Resume transfers of a paused token
+    pub fn unpause_token(
+        ctx: Context<SetPauseState>,
+        reason: String,
+    ) -> Result<()> {
+        require!(reason.len() <= 100, TokenLayerError::InvalidPauseReason);
+        
+        let pause_state = &mut ctx.accounts.pause_state;
+        require!(pause_state.is_paused, TokenLayerError::NotPaused);
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        pause_state.is_paused = false;
+        pause_state.last_reason = reason.clone();
+        pause_state.updated_at = timestamp;
+        
+        msg!("▶️  Transfers RESUMED for mint: {} ({})", pause_state.mint, reason);
+        
+        emit!(TokenUnpausedEvent {
+            mint: pause_state.mint,
+            authority: ctx.accounts.pause_authority.key(),
+            reason,
+            timestamp,
+        });
+        
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(name: String)]
-pub struct CreateToken2022WithHooks<'info> {
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + TokenInfo::SPACE,
-        seeds = [b"token_info", mint.key().as_ref()],
-        bump
-    )]
+    /// Put a token's administrative actions behind a timelock.
+    ///
+    /// The mint's TransferHook, mint and freeze authorities move to the timelock
+    /// PDA, so hook program swaps, minting and authority changes can only happen
+    /// through `execute_timelock_action`.
+    pub fn initialize_token_timelock(
+        ctx: Context<InitializeTokenTimelock>,
+        delay: i64,
+        guardian: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.token_info.creator == ctx.accounts.authority.key(),
+            TokenLayerError::Unauthorized
+        );
+        require!(
+            (Timelock::MIN_DELAY..=Timelock::MAX_DELAY).contains(&delay),
+            TokenLayerError::InvalidTimelockDelay
+        );
+        
+        let timelock = &mut ctx.accounts.timelock;
+        timelock.target = ctx.accounts.mint.key();
+        timelock.target_kind = TimelockTarget::Token;
+        timelock.admin = ctx.accounts.authority.key();
+        timelock.guardian = guardian;
+        timelock.delay = delay;
+        timelock.next_action_id = 0;
+        timelock.created_at = Clock::get()?.unix_timestamp;
+        timelock.bump = ctx.bumps.timelock;
+        
+        if ctx.accounts.token_info.has_transfer_hooks {
+            let set_authority_ix = set_authority(
+                &spl_token_2022::id(),
+                &ctx.accounts.mint.key(),
+                Some(&ctx.accounts.timelock.key()),
+                AuthorityType::TransferHookProgramId,
+                &ctx.accounts.authority.key(),
+                &[],
+            ).map_err(|_| TokenLayerError::HookSetupFailed)?;
+
+            invoke(
+                &set_authority_ix,
+                &[
+                    ctx.accounts.mint.to_account_info(),
+                    ctx.accounts.authority.to_account_info(),
+                    ctx.accounts.token_2022_program.to_account_info(),
+                ],
+            ).map_err(|_| TokenLayerError::HookSetupFailed)?;
+            
+            msg!("🔐 Transfer hook authority moved to timelock: {}", ctx.accounts.timelock.key());
+        }
+        
+        let (mint_authority, freeze_authority) = {
+            let mint_data = ctx.accounts.mint.try_borrow_data()?;
+            let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)
+                .map_err(|_| TokenLayerError::ExtensionParsingFailed)?;
+            (mint.base.mint_authority, mint.base.freeze_authority)
+        };
+        
+        for (current, authority_type) in [
+            (mint_authority, AuthorityType::MintTokens),
+            (freeze_authority, AuthorityType::FreezeAccount),
+        ] {
+            // An authority that was already renounced has nothing to protect
+            if let COption::Some(current) = current {
+                require_keys_eq!(current, ctx.accounts.authority.key(), TokenLayerError::Unauthorized);
+                set_mint_authority(
+                    &ctx.accounts.mint.to_account_info(),
+                    &ctx.accounts.authority.to_account_info(),
+                    Some(&ctx.accounts.timelock.key()),
+                    authority_type,
+                    &ctx.accounts.token_2022_program.to_account_info(),
+                    &[],
+                )?;
+            }
+        }
+        
+        msg!("🔐 Mint and freeze authorities moved to timelock: {}", ctx.accounts.timelock.key());
+        msg!("⏳ Timelock initialized for mint {} (delay: {}s, guardian: {})", 
+             ctx.accounts.mint.key(), delay, guardian);
+        Ok(())
+    }
+
+    /// Put creation fee and treasury changes behind a timelock
+    pub fn initialize_config_timelock(
+        ctx: Context<InitializeConfigTimelock>,
+        delay: i64,
+        guardian: Pubkey,
+    ) -> Result<()> {
+        require!(
+            (Timelock::MIN_DELAY..=Timelock::MAX_DELAY).contains(&delay),
+            TokenLayerError::InvalidTimelockDelay
+        );
+        
+        let timelock = &mut ctx.accounts.timelock;
+        timelock.target = ctx.accounts.config.key();
+        timelock.target_kind = TimelockTarget::Config;
+        timelock.admin = ctx.accounts.admin.key();
+        timelock.guardian = guardian;
+        timelock.delay = delay;
+        timelock.next_action_id = 0;
+        timelock.created_at = Clock::get()?.unix_timestamp;
+        timelock.bump = ctx.bumps.timelock;
+        
+        ctx.accounts.config.timelock = Some(ctx.accounts.timelock.key());
+        
+        msg!("⏳ Config timelock initialized (delay: {}s, guardian: {})", delay, guardian);
+        Ok(())
+    }
+
+    /// Queue an administrative action that can execute once its ETA has passed
+    pub fn propose_timelock_action(
+        ctx: Context<ProposeTimelockAction>,
+        kind: TimelockActionKind,
+    ) -> Result<()> {
+        let timelock = &mut ctx.accounts.timelock;
+        require!(
+            kind.allowed_on(timelock.target_kind),
+            TokenLayerError::InvalidTimelockAction
+        );
+        
+        let now = Clock::get()?.unix_timestamp;
+        let eta = now.checked_add(timelock.delay)
+            .ok_or(TokenLayerError::InvalidTimelockDelay)?;
+        
+        let action = &mut ctx.accounts.action;
+        action.timelock = timelock.key();
+        action.action_id = timelock.next_action_id;
+        action.kind = kind.clone();
+        action.proposer = ctx.accounts.admin.key();
+        action.proposed_at = now;
+        action.eta = eta;
+        action.status = TimelockActionStatus::Pending;
+        action.bump = ctx.bumps.action;
+        
+        timelock.next_action_id += 1;
+        
+        msg!("📝 Timelock action #{} proposed: {:?}", action.action_id, kind);
+        msg!("⏰ Executable after: {}", eta);
+        
+        emit!(TimelockActionProposedEvent {
+            timelock: action.timelock,
+            target: timelock.target,
+            action_id: action.action_id,
+            kind,
+            eta,
+        });
+        
+        Ok(())
+    }
+
+    /// Execute a queued action after its delay has elapsed
+    pub fn execute_timelock_action(
+        ctx: Context<ExecuteTimelockAction>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let action = &ctx.accounts.action;
+        
+        require!(
+            action.status == TimelockActionStatus::Pending,
+            TokenLayerError::TimelockActionNotPending
+        );
+        require!(now >= action.eta, TokenLayerError::TimelockNotReady);
+        require!(
+            now <= action.eta.saturating_add(Timelock::GRACE_PERIOD),
+            TokenLayerError::TimelockActionExpired
+        );
+        
+        let kind = action.kind.clone();
+        let action_id = action.action_id;
+        let target = ctx.accounts.timelock.target;
+        
+        match &kind {
+            TimelockActionKind::UpdateTransferHook { program_id } => {
+                let mint = ctx.accounts.mint.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                let token_2022_program = ctx.accounts.token_2022_program.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(mint.key(), target, TokenLayerError::InvalidTimelockAction);
+                
+                let update_ix = update_transfer_hook(
+                    &spl_token_2022::id(),
+                    &mint.key(),
+                    &ctx.accounts.timelock.key(),
+                    &[],
+                    *program_id,
+                ).map_err(|_| TokenLayerError::HookSetupFailed)?;
+
+                invoke_signed(
+                    &update_ix,
+                    &[
+                        mint.to_account_info(),
+                        ctx.accounts.timelock.to_account_info(),
+                        token_2022_program.to_account_info(),
+                    ],
+                    &[&[b"timelock", target.as_ref(), &[ctx.accounts.timelock.bump]]],
+                ).map_err(|_| TokenLayerError::HookSetupFailed)?;
+                
+                let token_info = ctx.accounts.token_info.as_mut()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(token_info.mint, target, TokenLayerError::InvalidTimelockAction);
+                token_info.hook_program_id = *program_id;
+                token_info.has_transfer_hooks = program_id.is_some();
+                
+                msg!("🔗 Transfer hook program updated: {:?}", program_id);
+            }
+            TimelockActionKind::ReleaseTransferHookAuthority { new_authority } => {
+                let mint = ctx.accounts.mint.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                let token_2022_program = ctx.accounts.token_2022_program.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(mint.key(), target, TokenLayerError::InvalidTimelockAction);
+                
+                let set_authority_ix = set_authority(
+                    &spl_token_2022::id(),
+                    &mint.key(),
+                    Some(new_authority),
+                    AuthorityType::TransferHookProgramId,
+                    &ctx.accounts.timelock.key(),
+                    &[],
+                ).map_err(|_| TokenLayerError::HookSetupFailed)?;
+
+                invoke_signed(
+                    &set_authority_ix,
+                    &[
+                        mint.to_account_info(),
+                        ctx.accounts.timelock.to_account_info(),
+                        token_2022_program.to_account_info(),
+                    ],
+                    &[&[b"timelock", target.as_ref(), &[ctx.accounts.timelock.bump]]],
+                ).map_err(|_| TokenLayerError::HookSetupFailed)?;
+                
+                msg!("🔓 Transfer hook authority released to: {}", new_authority);
+            }
+            TimelockActionKind::MintTokens { destination, amount } => {
+                let mint = ctx.accounts.mint.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                let destination_account = ctx.accounts.destination.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                let token_2022_program = ctx.accounts.token_2022_program.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(mint.key(), target, TokenLayerError::InvalidTimelockAction);
+                require_keys_eq!(destination_account.key(), *destination, TokenLayerError::InvalidTimelockAction);
+                
+                let mint_to_ix = mint_to(
+                    &spl_token_2022::id(),
+                    &mint.key(),
+                    destination,
+                    &ctx.accounts.timelock.key(),
+                    &[],
+                    *amount,
+                ).map_err(|_| TokenLayerError::TokenCreationFailed)?;
+
+                invoke_signed(
+                    &mint_to_ix,
+                    &[
+                        mint.to_account_info(),
+                        destination_account.to_account_info(),
+                        ctx.accounts.timelock.to_account_info(),
+                        token_2022_program.to_account_info(),
+                    ],
+                    &[&[b"timelock", target.as_ref(), &[ctx.accounts.timelock.bump]]],
+                ).map_err(|_| TokenLayerError::TokenCreationFailed)?;
+                
+                msg!("🔨 {} tokens minted to {}", amount, destination);
+            }
+            TimelockActionKind::ReleaseMintAuthority { new_authority }
+            | TimelockActionKind::ReleaseFreezeAuthority { new_authority } => {
+                let mint = ctx.accounts.mint.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                let token_2022_program = ctx.accounts.token_2022_program.as_ref()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(mint.key(), target, TokenLayerError::InvalidTimelockAction);
+                
+                let authority_type = match kind {
+                    TimelockActionKind::ReleaseMintAuthority { .. } => AuthorityType::MintTokens,
+                    _ => AuthorityType::FreezeAccount,
+                };
+                set_mint_authority(
+                    &mint.to_account_info(),
+                    &ctx.accounts.timelock.to_account_info(),
+                    new_authority.as_ref(),
+                    authority_type.clone(),
+                    &token_2022_program.to_account_info(),
+                    &[&[b"timelock", target.as_ref(), &[ctx.accounts.timelock.bump]]],
+                )?;
+                
+                msg!("🔓 {:?} authority released to: {:?}", authority_type, new_authority);
+            }
+            TimelockActionKind::SetPauseAuthority { new_authority } => {
+                let pause_state = ctx.accounts.pause_state.as_mut()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(pause_state.mint, target, TokenLayerError::InvalidTimelockAction);
+                pause_state.pause_authority = *new_authority;
+                
+                msg!("👤 Pause authority updated: {}", new_authority);
+            }
+            TimelockActionKind::SetCreationFee { creation_fee, fee_mint } => {
+                let config = ctx.accounts.config.as_mut()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(config.key(), target, TokenLayerError::InvalidTimelockAction);
+                config.creation_fee = *creation_fee;
+                config.fee_mint = *fee_mint;
+                
+                msg!("💸 Creation fee updated: {}", creation_fee);
+                
+                emit!(CreationFeeUpdatedEvent {
+                    creation_fee: *creation_fee,
+                    fee_mint: *fee_mint,
+                    timestamp: now,
+                });
+            }
+            TimelockActionKind::SetTreasury { treasury } => {
+                let config = ctx.accounts.config.as_mut()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(config.key(), target, TokenLayerError::InvalidTimelockAction);
+                let old_treasury = config.treasury;
+                config.treasury = *treasury;
+                
+                msg!("🏦 Treasury updated: {} -> {}", old_treasury, treasury);
+                
+                emit!(TreasuryUpdatedEvent {
+                    old_treasury,
+                    new_treasury: *treasury,
+                    timestamp: now,
+                });
+            }
+            TimelockActionKind::AddFeeWaiver { creator } => {
+                let config = ctx.accounts.config.as_mut()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(config.key(), target, TokenLayerError::InvalidTimelockAction);
+                config.add_fee_waiver(*creator)?;
+                
+                msg!("🤝 Fee waiver added for creator: {}", creator);
+                
+                emit!(FeeWaiverAddedEvent {
+                    creator: *creator,
+                    timestamp: now,
+                });
+            }
+            TimelockActionKind::RemoveFeeWaiver { creator } => {
+                let config = ctx.accounts.config.as_mut()
+                    .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                require_keys_eq!(config.key(), target, TokenLayerError::InvalidTimelockAction);
+                config.remove_fee_waiver(*creator)?;
+                
+                msg!("❌ Fee waiver removed for creator: {}", creator);
+                
+                emit!(FeeWaiverRemovedEvent {
+                    creator: *creator,
+                    timestamp: now,
+                });
+            }
+            TimelockActionKind::SetAdmin { new_admin } => {
+                if ctx.accounts.timelock.target_kind == TimelockTarget::Config {
+                    let config = ctx.accounts.config.as_mut()
+                        .ok_or(TokenLayerError::MissingTimelockAccount)?;
+                    require_keys_eq!(config.key(), target, TokenLayerError::InvalidTimelockAction);
+                    config.admin = *new_admin;
+                }
+                ctx.accounts.timelock.admin = *new_admin;
+                
+                msg!("👤 Timelock admin updated: {}", new_admin);
+            }
+        }
+        
+        ctx.accounts.action.status = TimelockActionStatus::Executed;
+        
+        msg!("✅ Timelock action #{} executed", action_id);
+        
+        emit!(TimelockActionExecutedEvent {
+            timelock: ctx.accounts.timelock.key(),
+            target,
+            action_id,
+            kind,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+
+    /// Guardian veto of a queued action
+    pub fn cancel_timelock_action(
+        ctx: Context<CancelTimelockAction>,
+    ) -> Result<()> {
+        let action = &mut ctx.accounts.action;
+        require!(
+            action.status == TimelockActionStatus::Pending,
+            TokenLayerError::TimelockActionNotPending
+        );
+        
+        action.status = TimelockActionStatus::Cancelled;
+        
+        msg!("🛑 Timelock action #{} cancelled by guardian {}", 
+             action.action_id, ctx.accounts.guardian.key());
+        
+        emit!(TimelockActionCancelledEvent {
+            timelock: action.timelock,
+            action_id: action.action_id,
+            guardian: ctx.accounts.guardian.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Get comprehensive token information
+    pub fn get_token_info(ctx: Context<GetTokenInfo>) -> Result<()> {
+        let token_info = &ctx.accounts.token_info;
+        
+        msg!("📋 Token Information:");
+        msg!("🏷️  Name: {}", token_info.name);
+        msg!("🔤 Symbol: {}", token_info.symbol);
+        msg!("🔢 Decimals: {}", token_info.decimals);
+        msg!("🪙 Mint: {}", token_info.mint);
+        msg!("🔗 Hook Program: {:?}", token_info.hook_program_id);
+        msg!("👤 Creator: {}", token_info.creator);
+        msg!("📊 Supply: {}", token_info.total_supply);
+        msg!("🔒 Has Hooks: {}", token_info.has_transfer_hooks);
+        msg!("⚙️  Token Program: {}", token_info.token_program_id);
+        
+        Ok(())
+    }
+
+    /// Grow a `TokenInfo` created before `has_confidential_transfers` existed
+    /// to the current size.
+    ///
+    /// Strings are stored at their actual length, so most legacy accounts already
+    /// have a zero byte where the flag now sits. One whose name and symbol are at
+    /// their 50 and 10 byte maximums ends right after `token_program_id` and can't
+    /// be loaded until it grows. The new byte is zeroed, i.e. `false`, which is
+    /// correct for every token created before confidential transfer support.
+    pub fn migrate_token_info(ctx: Context<MigrateTokenInfo>) -> Result<()> {
+        let token_info = ctx.accounts.token_info.to_account_info();
+        let new_len = 8 + TokenInfo::SPACE;
+        require!(
+            token_info.data_len() < new_len,
+            TokenLayerError::TokenInfoAlreadyMigrated
+        );
+        {
+            let data = token_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == TokenInfo::DISCRIMINATOR,
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+        }
+        
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(token_info.lamports());
+        if rent_due > 0 {
+            invoke(
+                &system_instruction::transfer(ctx.accounts.payer.key, token_info.key, rent_due),
+                &[
+                    ctx.accounts.payer.to_account_info(),
+                    token_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        token_info.realloc(new_len, true)?;
+        
+        msg!("📦 TokenInfo for mint {} migrated to {} bytes", ctx.accounts.mint.key(), new_len);
+        Ok(())
+    }
+}
+
+/// Optional accounts used to pay the creation fee
+pub struct CreationFeeAccounts<'a, 'info> {
+    pub treasury: Option<&'a UncheckedAccount<'info>>,
+    pub fee_source: Option<&'a UncheckedAccount<'info>>,
+    pub treasury_token_account: Option<&'a UncheckedAccount<'info>>,
+    pub fee_mint: Option<&'a UncheckedAccount<'info>>,
+    pub fee_token_program: Option<&'a UncheckedAccount<'info>>,
+}
+
+/// Charge the configured creation fee from `payer` to the treasury.
+///
+/// Paid in SOL when `config.fee_mint` is `None`, otherwise as a
+/// `transfer_checked` of the fee mint into a treasury-owned token account.
+fn charge_creation_fee<'info>(
+    config: &Account<'info, TokenLayerConfig>,
+    mint: &Pubkey,
+    creator: &Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    fee_accounts: CreationFeeAccounts<'_, 'info>,
+) -> Result<()> {
+    if config.creation_fee == 0 {
+        return Ok(());
+    }
+    
+    if config.fee_waivers.contains(creator) {
+        msg!("🤝 Creation fee waived for partner creator: {}", creator);
+        return Ok(());
+    }
+    
+    match config.fee_mint {
+        None => {
+            let treasury = fee_accounts.treasury
+                .ok_or(TokenLayerError::MissingFeeAccount)?;
+            
+            let transfer_ix = system_instruction::transfer(
+                payer.key,
+                treasury.key,
+                config.creation_fee,
+            );
+            
+            invoke(
+                &transfer_ix,
+                &[
+                    payer.clone(),
+                    treasury.to_account_info(),
+                    system_program.clone(),
+                ],
+            ).map_err(|_| TokenLayerError::FeePaymentFailed)?;
+        }
+        Some(fee_mint_key) => {
+            let fee_source = fee_accounts.fee_source
+                .ok_or(TokenLayerError::MissingFeeAccount)?;
+            let treasury_token_account = fee_accounts.treasury_token_account
+                .ok_or(TokenLayerError::MissingFeeAccount)?;
+            let fee_mint = fee_accounts.fee_mint
+                .ok_or(TokenLayerError::MissingFeeAccount)?;
+            let fee_token_program = fee_accounts.fee_token_program
+                .ok_or(TokenLayerError::MissingFeeAccount)?;
+            
+            require_keys_eq!(fee_mint.key(), fee_mint_key, TokenLayerError::InvalidFeeMint);
+            require!(
+                fee_token_program.key() == spl_token::id()
+                    || fee_token_program.key() == spl_token_2022::id(),
+                TokenLayerError::InvalidTokenProgram
+            );
+            require!(
+                fee_mint.owner == fee_token_program.key,
+                TokenLayerError::InvalidFeeMint
+            );
+            
+            // Treasury token account must belong to the configured treasury
+            {
+                let data = treasury_token_account.try_borrow_data()?;
+                let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)
+                    .map_err(|_| TokenLayerError::InvalidTreasury)?;
+                require_keys_eq!(account.base.owner, config.treasury, TokenLayerError::InvalidTreasury);
+                require_keys_eq!(account.base.mint, fee_mint_key, TokenLayerError::InvalidFeeMint);
+            }
+            
+            let decimals = {
+                let data = fee_mint.try_borrow_data()?;
+                StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)
+                    .map_err(|_| TokenLayerError::InvalidFeeMint)?
+                    .base
+                    .decimals
+            };
+            
+            let transfer_ix = transfer_checked(
+                fee_token_program.key,
+                fee_source.key,
+                fee_mint.key,
+                treasury_token_account.key,
+                payer.key,
+                &[],
+                config.creation_fee,
+                decimals,
+            ).map_err(|_| TokenLayerError::FeePaymentFailed)?;
+            
+            invoke(
+                &transfer_ix,
+                &[
+                    fee_source.to_account_info(),
+                    fee_mint.to_account_info(),
+                    treasury_token_account.to_account_info(),
+                    payer.clone(),
+                    fee_token_program.to_account_info(),
+                ],
+            ).map_err(|_| TokenLayerError::FeePaymentFailed)?;
+        }
+    }
+    
+    msg!("💸 Creation fee paid: {} -> treasury {}", config.creation_fee, config.treasury);
+    
+    emit!(CreationFeePaidEvent {
+        mint: *mint,
+        creator: *creator,
+        amount: config.creation_fee,
+        fee_mint: config.fee_mint,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Reject wallets whose kyc-hook record would fail the transfer hook
+fn check_kyc_compliance(record: &UncheckedAccount, wallet: &Pubkey) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"kyc_record", wallet.as_ref()],
+        &kyc_hook::ID,
+    );
+    require_keys_eq!(record.key(), expected, TokenLayerError::InvalidComplianceAccount);
+    
+    if record.owner != &kyc_hook::ID || record.data_is_empty() {
+        msg!("❌ No KYC record for wallet: {}", wallet);
+        return Err(TokenLayerError::WalletNotKycVerified.into());
+    }
+    
+    let data = record.try_borrow_data()?;
+    let kyc_record = KYCRecord::try_deserialize(&mut &data[..])
+        .map_err(|_| TokenLayerError::InvalidComplianceAccount)?;
+    
+    // Same rule as the hook: verified with a Basic or Enhanced level
+    if !kyc_record.is_verified || !(1..=2).contains(&kyc_record.kyc_level) {
+        msg!("❌ Wallet {} not KYC verified (level {})", wallet, kyc_record.kyc_level);
+        return Err(TokenLayerError::WalletNotKycVerified.into());
+    }
+    
+    msg!("✅ KYC pre-check passed for wallet: {}", wallet);
+    Ok(())
+}
+
+/// Reject wallets the whitelist-hook would block
+fn check_whitelist_compliance(whitelist: &UncheckedAccount, wallet: &Pubkey) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[b"whitelist"], &whitelist_hook::ID);
+    require_keys_eq!(whitelist.key(), expected, TokenLayerError::InvalidComplianceAccount);
+    require!(
+        whitelist.owner == &whitelist_hook::ID,
+        TokenLayerError::InvalidComplianceAccount
+    );
+    
+    let data = whitelist.try_borrow_data()?;
+    let whitelist = Whitelist::try_deserialize(&mut &data[..])
+        .map_err(|_| TokenLayerError::InvalidComplianceAccount)?;
+    
+    if whitelist.is_enabled && !whitelist.approved_addresses.contains(wallet) {
+        msg!("❌ Wallet {} not whitelisted", wallet);
+        return Err(TokenLayerError::WalletNotWhitelisted.into());
+    }
+    
+    msg!("✅ Whitelist pre-check passed for wallet: {}", wallet);
+    Ok(())
+}
+
+/// Move one of the mint's base authorities (`None` renounces it)
+fn set_mint_authority<'info>(
+    mint: &AccountInfo<'info>,
+    current_authority: &AccountInfo<'info>,
+    new_authority: Option<&Pubkey>,
+    authority_type: AuthorityType,
+    token_2022_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let set_authority_ix = set_authority(
+        &spl_token_2022::id(),
+        mint.key,
+        new_authority,
+        authority_type,
+        current_authority.key,
+        &[],
+    ).map_err(|_| TokenLayerError::AuthorityTransferFailed)?;
+
+    invoke_signed(
+        &set_authority_ix,
+        &[mint.clone(), current_authority.clone(), token_2022_program.clone()],
+        signer_seeds,
+    ).map_err(|_| TokenLayerError::AuthorityTransferFailed)?;
+    Ok(())
+}
+
+/// Build the ConfidentialTransfer `InitializeMint` instruction.
+///
+/// The spl-token-2022 builder is compiled out for on-chain targets, so the
+/// instruction data is encoded here: `[27, 0, authority(32), auto_approve(1), auditor(32)]`.
+fn initialize_confidential_transfer_mint(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    config: &ConfidentialTransferConfig,
+) -> anchor_lang::solana_program::instruction::Instruction {
+    let mut data = TokenInstruction::ConfidentialTransferExtension.pack();
+    data.push(ConfidentialTransferInstruction::InitializeMint as u8);
+    data.extend_from_slice(authority.as_ref());
+    data.push(config.auto_approve_new_accounts as u8);
+    data.extend_from_slice(&config.auditor_elgamal_pubkey.unwrap_or([0u8; 32]));
+
+    anchor_lang::solana_program::instruction::Instruction {
+        program_id: spl_token_2022::id(),
+        accounts: vec![
+            anchor_lang::solana_program::instruction::AccountMeta::new(*mint, false),
+        ],
+        data,
+    }
+}
+
+// ========== ACCOUNT STRUCTURES - FIXED ==========
+
+#[derive(Accounts)]
+pub struct Initialize {}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TokenLayerConfig::SPACE,
+        seeds = [b"token_layer_config"],
+        bump
+    )]
+    pub config: Account<'info, TokenLayerConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub program: Program<'info, crate::program::TokenLayer>,
+    
+    /// CHECK: must be this program's ProgramData; its upgrade authority is checked in the handler
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program_data: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_layer_config"],
+        bump = config.bump,
+        has_one = admin @ TokenLayerError::Unauthorized
+    )]
+    pub config: Account<'info, TokenLayerConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateToken2022WithHooks<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TokenInfo::SPACE,
+        seeds = [b"token_info", mint.key().as_ref()],
+        bump
+    )]
     pub token_info: Account<'info, TokenInfo>,
     
     /// The mint account to be created (must be Keypair.generate())
@@ -389,6 +1628,30 @@ pub struct CreateToken2022WithHooks<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    #[account(
+        seeds = [b"token_layer_config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, TokenLayerConfig>,
+    
+    /// CHECK: SOL treasury, required when the creation fee is paid in SOL
+    #[account(mut, address = config.treasury @ TokenLayerError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Payer's fee token account, required when the fee is paid in an SPL token
+    #[account(mut)]
+    pub fee_source: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Treasury-owned fee token account, validated in `charge_creation_fee`
+    #[account(mut)]
+    pub treasury_token_account: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Fee mint, must match `config.fee_mint`
+    pub fee_mint: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token program owning the fee mint (SPL Token or Token-2022)
+    pub fee_token_program: Option<UncheckedAccount<'info>>,
+    
     pub system_program: Program<'info, System>,
     pub token_2022_program: Program<'info, Token2022>,
     pub rent: Sysvar<'info, Rent>,
@@ -414,11 +1677,106 @@ pub struct CreateBasicToken2022<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    #[account(
+        seeds = [b"token_layer_config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, TokenLayerConfig>,
+    
+    /// CHECK: SOL treasury, required when the creation fee is paid in SOL
+    #[account(mut, address = config.treasury @ TokenLayerError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Payer's fee token account, required when the fee is paid in an SPL token
+    #[account(mut)]
+    pub fee_source: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Treasury-owned fee token account, validated in `charge_creation_fee`
+    #[account(mut)]
+    pub treasury_token_account: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Fee mint, must match `config.fee_mint`
+    pub fee_mint: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token program owning the fee mint (SPL Token or Token-2022)
+    pub fee_token_program: Option<UncheckedAccount<'info>>,
+    
     pub system_program: Program<'info, System>,
     pub token_2022_program: Program<'info, Token2022>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateConfidentialToken2022<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TokenInfo::SPACE,
+        seeds = [b"token_info", mint.key().as_ref()],
+        bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+    
+    #[account(mut)]
+    pub mint: Signer<'info>,
+    
+    /// Authority for the mint and the confidential transfer configuration
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        seeds = [b"token_layer_config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, TokenLayerConfig>,
+    
+    /// CHECK: SOL treasury, required when the creation fee is paid in SOL
+    #[account(mut, address = config.treasury @ TokenLayerError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Payer's fee token account, required when the fee is paid in an SPL token
+    #[account(mut)]
+    pub fee_source: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Treasury-owned fee token account, validated in `charge_creation_fee`
+    #[account(mut)]
+    pub treasury_token_account: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Fee mint, must match `config.fee_mint`
+    pub fee_mint: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token program owning the fee mint (SPL Token or Token-2022)
+    pub fee_token_program: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveConfidentialAccount<'info> {
+    #[account(
+        seeds = [b"token_info", mint.key().as_ref()],
+        bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+    
+    /// CHECK: Confidential Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    
+    /// CHECK: Token account configured for confidential transfers
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+    
+    /// Confidential transfer authority of the mint
+    pub authority: Signer<'info>,
+    
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
 #[derive(Accounts)]
 pub struct CreateAssociatedTokenAccount<'info> {
     #[account(mut)]
@@ -438,6 +1796,45 @@ pub struct CreateAssociatedTokenAccount<'info> {
     pub token_2022_program: Program<'info, Token2022>,
 }
 
+#[derive(Accounts)]
+pub struct CreateCompliantAssociatedTokenAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Wallet that will own the token account (must sign when extensions are requested)
+    pub wallet: UncheckedAccount<'info>,
+    
+    /// CHECK: The mint for the token account
+    #[account(address = token_info.mint)]
+    pub mint: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"token_info", mint.key().as_ref()],
+        bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+    
+    /// CHECK: Associated token account to be created
+    #[account(
+        mut,
+        address = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &wallet.key(),
+            &mint.key(),
+            &spl_token_2022::id(),
+        )
+    )]
+    pub associated_token: UncheckedAccount<'info>,
+    
+    /// CHECK: KYC record (kyc-hook) or whitelist (whitelist-hook) for the wallet
+    pub compliance_account: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_2022_program: Program<'info, Token2022>,
+    /// CHECK: Associated token account program
+    #[account(address = spl_associated_token_account::id())]
+    pub associated_token_program: UncheckedAccount<'info>,
+}
+
 #[derive(Accounts)]
 pub struct MintTokens<'info> {
     /// CHECK: Token mint account (we'll verify it exists)
@@ -459,6 +1856,196 @@ pub struct CheckTransferHookExtension<'info> {
     pub mint: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct InitializePauseState<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PauseState::SPACE,
+        seeds = [PAUSE_STATE_SEED, token_info.mint.as_ref()],
+        bump
+    )]
+    pub pause_state: Account<'info, PauseState>,
+    
+    #[account(
+        seeds = [b"token_info", token_info.mint.as_ref()],
+        bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+    
+    /// Token creator
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseState<'info> {
+    #[account(
+        mut,
+        seeds = [PAUSE_STATE_SEED, pause_state.mint.as_ref()],
+        bump = pause_state.bump,
+        has_one = pause_authority @ TokenLayerError::Unauthorized
+    )]
+    pub pause_state: Account<'info, PauseState>,
+    
+    pub pause_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTokenTimelock<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Timelock::SPACE,
+        seeds = [b"timelock", mint.key().as_ref()],
+        bump
+    )]
+    pub timelock: Account<'info, Timelock>,
+    
+    #[account(
+        seeds = [b"token_info", mint.key().as_ref()],
+        bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+    
+    /// CHECK: Token-2022 mint of the token
+    #[account(mut, address = token_info.mint)]
+    pub mint: UncheckedAccount<'info>,
+    
+    /// Token creator and current transfer hook, mint and freeze authority
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfigTimelock<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Timelock::SPACE,
+        seeds = [b"timelock", config.key().as_ref()],
+        bump
+    )]
+    pub timelock: Account<'info, Timelock>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_layer_config"],
+        bump = config.bump,
+        has_one = admin @ TokenLayerError::Unauthorized
+    )]
+    pub config: Account<'info, TokenLayerConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeTimelockAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"timelock", timelock.target.as_ref()],
+        bump = timelock.bump,
+        has_one = admin @ TokenLayerError::Unauthorized
+    )]
+    pub timelock: Account<'info, Timelock>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TimelockAction::SPACE,
+        seeds = [
+            b"timelock_action",
+            timelock.key().as_ref(),
+            &timelock.next_action_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub action: Account<'info, TimelockAction>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTimelockAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"timelock", timelock.target.as_ref()],
+        bump = timelock.bump
+    )]
+    pub timelock: Account<'info, Timelock>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"timelock_action",
+            timelock.key().as_ref(),
+            &action.action_id.to_le_bytes()
+        ],
+        bump = action.bump,
+        has_one = timelock
+    )]
+    pub action: Account<'info, TimelockAction>,
+    
+    /// Anyone may execute once the delay has elapsed
+    pub executor: Signer<'info>,
+    
+    /// CHECK: Token-2022 mint (token actions), must match the timelock target
+    #[account(mut)]
+    pub mint: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token account receiving a `MintTokens` action, must match the queued destination
+    #[account(mut)]
+    pub destination: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub token_info: Option<Account<'info, TokenInfo>>,
+    
+    #[account(mut)]
+    pub pause_state: Option<Account<'info, PauseState>>,
+    
+    #[account(mut)]
+    pub config: Option<Account<'info, TokenLayerConfig>>,
+    
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTimelockAction<'info> {
+    #[account(
+        seeds = [b"timelock", timelock.target.as_ref()],
+        bump = timelock.bump,
+        has_one = guardian @ TokenLayerError::Unauthorized
+    )]
+    pub timelock: Account<'info, Timelock>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"timelock_action",
+            timelock.key().as_ref(),
+            &action.action_id.to_le_bytes()
+        ],
+        bump = action.bump,
+        has_one = timelock
+    )]
+    pub action: Account<'info, TimelockAction>,
+    
+    pub guardian: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct GetTokenInfo<'info> {
     #[account(
@@ -468,6 +2055,28 @@ pub struct GetTokenInfo<'info> {
     pub token_info: Account<'info, TokenInfo>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateTokenInfo<'info> {
+    /// CHECK: Legacy `TokenInfo`, too short to deserialize as the current layout;
+    /// the discriminator is checked in the handler
+    #[account(
+        mut,
+        seeds = [b"token_info", mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub token_info: UncheckedAccount<'info>,
+    
+    /// CHECK: Only used to derive the `TokenInfo` address
+    pub mint: UncheckedAccount<'info>,
+    
+    /// Anyone may pay for the migration
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
 // ========== DATA STRUCTURES - FIXED ==========
 
 #[account]
@@ -482,10 +2091,147 @@ pub struct TokenInfo {
     pub total_supply: u64,               // 8 bytes
     pub has_transfer_hooks: bool,        // 1 byte
     pub token_program_id: Pubkey,        // 32 bytes
+    pub has_confidential_transfers: bool, // 1 byte - added later, see `migrate_token_info`
 }
 
 impl TokenInfo {
-    pub const SPACE: usize = 54 + 14 + 1 + 32 + 33 + 8 + 32 + 8 + 1 + 32; // 215 bytes
+    pub const SPACE: usize = 54 + 14 + 1 + 32 + 33 + 8 + 32 + 8 + 1 + 32 + 1; // 216 bytes
+}
+
+/// ConfidentialTransferMint settings supplied at mint creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfidentialTransferConfig {
+    pub auto_approve_new_accounts: bool,
+    pub auditor_elgamal_pubkey: Option<[u8; 32]>,
+}
+
+/// Global token layer configuration: creation fee, treasury and partner waivers
+#[account]
+pub struct TokenLayerConfig {
+    pub admin: Pubkey,                   // 32 bytes
+    pub treasury: Pubkey,                // 32 bytes
+    pub creation_fee: u64,               // 8 bytes
+    pub fee_mint: Option<Pubkey>,        // 1 + 32 = 33 bytes (None = SOL)
+    pub fee_waivers: Vec<Pubkey>,        // 4 + (20 * 32) = 644 bytes
+    pub timelock: Option<Pubkey>,        // 1 + 32 = 33 bytes
+    pub created_at: i64,                 // 8 bytes
+    pub bump: u8,                        // 1 byte
+}
+
+impl TokenLayerConfig {
+    pub const MAX_FEE_WAIVERS: usize = 20;
+    pub const SPACE: usize = 32 + 32 + 8 + 33 + 644 + 33 + 8 + 1; // 791 bytes
+
+    pub fn add_fee_waiver(&mut self, creator: Pubkey) -> Result<()> {
+        require!(
+            !self.fee_waivers.contains(&creator),
+            TokenLayerError::FeeWaiverAlreadyExists
+        );
+        require!(
+            self.fee_waivers.len() < Self::MAX_FEE_WAIVERS,
+            TokenLayerError::FeeWaiverListFull
+        );
+        self.fee_waivers.push(creator);
+        Ok(())
+    }
+
+    pub fn remove_fee_waiver(&mut self, creator: Pubkey) -> Result<()> {
+        let position = self.fee_waivers.iter().position(|&x| x == creator)
+            .ok_or(TokenLayerError::FeeWaiverNotFound)?;
+        self.fee_waivers.remove(position);
+        Ok(())
+    }
+}
+
+/// Account-level extensions applied by `create_compliant_associated_token_account`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AccountExtensionOptions {
+    pub require_memo_transfers: bool,
+    pub reserve_cpi_guard: bool,
+}
+
+/// Delay queue for administrative actions on a token (or on the global config)
+#[account]
+pub struct Timelock {
+    pub target: Pubkey,                  // 32 bytes - mint or config
+    pub target_kind: TimelockTarget,     // 1 byte
+    pub admin: Pubkey,                   // 32 bytes
+    pub guardian: Pubkey,                // 32 bytes
+    pub delay: i64,                      // 8 bytes
+    pub next_action_id: u64,             // 8 bytes
+    pub created_at: i64,                 // 8 bytes
+    pub bump: u8,                        // 1 byte
+}
+
+impl Timelock {
+    pub const MIN_DELAY: i64 = 24 * 60 * 60;        // 1 day
+    pub const MAX_DELAY: i64 = 30 * 24 * 60 * 60;   // 30 days
+    pub const GRACE_PERIOD: i64 = 14 * 24 * 60 * 60; // 14 days to execute after ETA
+    pub const SPACE: usize = 32 + 1 + 32 + 32 + 8 + 8 + 8 + 1; // 122 bytes
+}
+
+/// A queued administrative action
+#[account]
+pub struct TimelockAction {
+    pub timelock: Pubkey,                // 32 bytes
+    pub action_id: u64,                  // 8 bytes
+    pub kind: TimelockActionKind,        // 1 + 41 = 42 bytes
+    pub proposer: Pubkey,                // 32 bytes
+    pub proposed_at: i64,                // 8 bytes
+    pub eta: i64,                        // 8 bytes
+    pub status: TimelockActionStatus,    // 1 byte
+    pub bump: u8,                        // 1 byte
+}
+
+impl TimelockAction {
+    pub const SPACE: usize = 32 + 8 + 42 + 32 + 8 + 8 + 1 + 1; // 132 bytes
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimelockTarget {
+    Token,
+    Config,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum TimelockActionKind {
+    UpdateTransferHook { program_id: Option<Pubkey> },
+    ReleaseTransferHookAuthority { new_authority: Pubkey },
+    SetPauseAuthority { new_authority: Pubkey },
+    MintTokens { destination: Pubkey, amount: u64 },
+    ReleaseMintAuthority { new_authority: Option<Pubkey> },
+    ReleaseFreezeAuthority { new_authority: Option<Pubkey> },
+    SetCreationFee { creation_fee: u64, fee_mint: Option<Pubkey> },
+    SetTreasury { treasury: Pubkey },
+    AddFeeWaiver { creator: Pubkey },
+    RemoveFeeWaiver { creator: Pubkey },
+    SetAdmin { new_admin: Pubkey },
+}
+
+impl TimelockActionKind {
+    /// Whether the action may be queued on a timelock of `target` kind
+    fn allowed_on(&self, target: TimelockTarget) -> bool {
+        match self {
+            Self::UpdateTransferHook { .. }
+            | Self::ReleaseTransferHookAuthority { .. }
+            | Self::SetPauseAuthority { .. }
+            | Self::MintTokens { .. }
+            | Self::ReleaseMintAuthority { .. }
+            | Self::ReleaseFreezeAuthority { .. } => target == TimelockTarget::Token,
+            Self::SetCreationFee { .. }
+            | Self::SetTreasury { .. }
+            | Self::AddFeeWaiver { .. }
+            | Self::RemoveFeeWaiver { .. } => target == TimelockTarget::Config,
+            Self::SetAdmin { .. } => true,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimelockActionStatus {
+    Pending,
+    Executed,
+    Cancelled,
 }
 
 // ========== EVENTS - NEW ==========
@@ -500,6 +2246,83 @@ pub struct TokenCreatedEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct TokenPausedEvent {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenUnpausedEvent {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreationFeePaidEvent {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub fee_mint: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreationFeeUpdatedEvent {
+    pub creation_fee: u64,
+    pub fee_mint: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryUpdatedEvent {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeWaiverAddedEvent {
+    pub creator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeWaiverRemovedEvent {
+    pub creator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TimelockActionProposedEvent {
+    pub timelock: Pubkey,
+    pub target: Pubkey,
+    pub action_id: u64,
+    pub kind: TimelockActionKind,
+    pub eta: i64,
+}
+
+#[event]
+pub struct TimelockActionExecutedEvent {
+    pub timelock: Pubkey,
+    pub target: Pubkey,
+    pub action_id: u64,
+    pub kind: TimelockActionKind,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TimelockActionCancelledEvent {
+    pub timelock: Pubkey,
+    pub action_id: u64,
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
+
 // ========== ERRORS - IMPROVED ==========
 
 #[error_code]
@@ -524,4 +2347,58 @@ pub enum TokenLayerError {
     InvalidDecimals,
     #[msg("Invalid supply amount")]
     InvalidSupply,
+    #[msg("Confidential transfer setup failed")]
+    ConfidentialTransferSetupFailed,
+    #[msg("Confidential transfers are not enabled for this token")]
+    ConfidentialTransfersNotEnabled,
+    #[msg("Unauthorized access")]
+    Unauthorized,
+    #[msg("Pause reason too long")]
+    InvalidPauseReason,
+    #[msg("Token is already paused")]
+    AlreadyPaused,
+    #[msg("Token is not paused")]
+    NotPaused,
+    #[msg("Missing account required to pay the creation fee")]
+    MissingFeeAccount,
+    #[msg("Creation fee payment failed")]
+    FeePaymentFailed,
+    #[msg("Invalid fee mint")]
+    InvalidFeeMint,
+    #[msg("Invalid treasury account")]
+    InvalidTreasury,
+    #[msg("Creator already has a fee waiver")]
+    FeeWaiverAlreadyExists,
+    #[msg("Creator has no fee waiver")]
+    FeeWaiverNotFound,
+    #[msg("Fee waiver list is at maximum capacity")]
+    FeeWaiverListFull,
+    #[msg("Compliance account required for this mint's hook")]
+    MissingComplianceAccount,
+    #[msg("Invalid compliance account")]
+    InvalidComplianceAccount,
+    #[msg("Wallet is not KYC verified for this token")]
+    WalletNotKycVerified,
+    #[msg("Wallet is not whitelisted for this token")]
+    WalletNotWhitelisted,
+    #[msg("Wallet must sign to enable account extensions")]
+    WalletSignatureRequired,
+    #[msg("Change must go through the timelock")]
+    TimelockRequired,
+    #[msg("Timelock delay out of range")]
+    InvalidTimelockDelay,
+    #[msg("Action not valid for this timelock")]
+    InvalidTimelockAction,
+    #[msg("Timelock action is not pending")]
+    TimelockActionNotPending,
+    #[msg("Timelock delay has not elapsed")]
+    TimelockNotReady,
+    #[msg("Timelock action grace period has expired")]
+    TimelockActionExpired,
+    #[msg("Missing account required to execute the timelock action")]
+    MissingTimelockAccount,
+    #[msg("Failed to move mint authority")]
+    AuthorityTransferFailed,
+    #[msg("TokenInfo already has the current layout")]
+    TokenInfoAlreadyMigrated,
 }
\ No newline at end of file
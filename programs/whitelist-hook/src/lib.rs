@@ -134,17 +134,7 @@ pub mod whitelist_hook {
     ) -> Result<()> {
         msg!("🚀 Initializing REAL ExtraAccountMetaList for mint: {}", ctx.accounts.mint.key());
         
-        // Define the extra accounts needed for whitelist validation
-        let account_metas = vec![
-            // Whitelist account for validation
-            ExtraAccountMeta::new_with_seeds(
-                &[Seed::Literal {
-                    bytes: b"whitelist".to_vec(),
-                }],
-                false, // is_signer
-                true,  // is_writable (to update transfer stats)
-            )?,
-        ];
+        let account_metas = extra_account_metas()?;
 
         // Calculate account size needed
         let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
@@ -162,6 +152,45 @@ pub mod whitelist_hook {
         Ok(())
     }
 
+    /// Rewrite an existing ExtraAccountMetaList with the current extra accounts.
+    ///
+    /// Lists created before the pause state existed lack its entries, so transfers
+    /// of their mint ignore a pause until this runs. The contents are fixed by the
+    /// program, so anyone may pay for the update.
+    pub fn update_extra_account_meta_list(
+        ctx: Context<UpdateExtraAccountMetaList>,
+    ) -> Result<()> {
+        let account_metas = extra_account_metas()?;
+        let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
+        let meta_list = ctx.accounts.extra_account_meta_list.to_account_info();
+        
+        if meta_list.data_len() < account_size {
+            let rent_due = Rent::get()?
+                .minimum_balance(account_size)
+                .saturating_sub(meta_list.lamports());
+            if rent_due > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.payer.to_account_info(),
+                            to: meta_list.clone(),
+                        },
+                    ),
+                    rent_due,
+                )?;
+            }
+            meta_list.realloc(account_size, false)?;
+        }
+        
+        let mut data = meta_list.try_borrow_mut_data()?;
+        ExtraAccountMetaList::update::<ExecuteInstruction>(&mut data, &account_metas)?;
+        
+        msg!("🔄 ExtraAccountMetaList for mint {} updated with {} extra accounts",
+             ctx.accounts.mint.key(), account_metas.len());
+        Ok(())
+    }
+
     /// Transfer Hook Execute - REAL implementation called by Token-2022
     pub fn transfer_hook_execute(
         ctx: Context<TransferHookExecute>,
@@ -173,6 +202,14 @@ pub mod whitelist_hook {
         msg!("📦 Source: {}", ctx.accounts.source_token.key());
         msg!("📦 Destination: {}", ctx.accounts.destination_token.key());
 
+        // Reject everything while the issuer has paused the token
+        if let Some(pause_state) = &ctx.accounts.pause_state {
+            if token_layer_pause::is_transfer_paused(pause_state, &ctx.accounts.mint.key())? {
+                msg!("❌ Transfer BLOCKED: Token {} is paused", ctx.accounts.mint.key());
+                return Err(WhitelistError::TokenPaused.into());
+            }
+        }
+
         let whitelist = &mut ctx.accounts.whitelist;
 
         // Check if whitelist validation is enabled
@@ -241,6 +278,14 @@ pub mod whitelist_hook {
                 msg!("👤 Owner: {}", owner.key());
                 msg!("💰 Amount: {}", amount);
 
+                // Pause state is only present for meta lists created or updated with it
+                if let Some(pause_state) = accounts.get(7) {
+                    if token_layer_pause::is_transfer_paused(pause_state, mint.key)? {
+                        msg!("❌ Token {} is paused - Transfer BLOCKED", mint.key());
+                        return Err(WhitelistError::TokenPaused.into());
+                    }
+                }
+
                 // Load and validate whitelist
                 let whitelist_data = whitelist_account.try_borrow_data()
                     .map_err(|_| WhitelistError::InvalidWhitelistAccount)?;
@@ -302,6 +347,34 @@ pub mod whitelist_hook {
     }
 }
 
+/// Extra accounts the whitelist hook needs on every transfer
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![
+        // Whitelist account for validation
+        ExtraAccountMeta::new_with_seeds(
+            &[Seed::Literal {
+                bytes: b"whitelist".to_vec(),
+            }],
+            false, // is_signer
+            true,  // is_writable (to update transfer stats)
+        )?,
+        // Token layer program that owns the per-mint pause state
+        ExtraAccountMeta::new_with_pubkey(&token_layer_pause::ID, false, false)?,
+        // Pause state PDA for this mint, derived from the token layer program (index 6)
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            6,
+            &[
+                Seed::Literal {
+                    bytes: token_layer_pause::PAUSE_STATE_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // Index 1 is the mint
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+    ])
+}
+
 // ========== ACCOUNT STRUCTURES ==========
 
 #[derive(Accounts)]
@@ -352,6 +425,26 @@ pub struct InitializeExtraAccountMetaList<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: ExtraAccountMetaList Account, rewritten in place
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    
+    /// CHECK: The mint that this hook is for
+    pub mint: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
 /// Transfer Hook Execute - Called by Token-2022 during transfers
 #[derive(Accounts)]
 pub struct TransferHookExecute<'info> {
@@ -371,6 +464,13 @@ pub struct TransferHookExecute<'info> {
         bump
     )]
     pub whitelist: Account<'info, Whitelist>,
+    
+    /// CHECK: Token layer program
+    #[account(address = token_layer_pause::ID)]
+    pub token_layer_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token layer pause state for the mint, validated by `is_transfer_paused`
+    pub pause_state: Option<UncheckedAccount<'info>>,
 }
 
 /// Get Whitelist Statistics
@@ -410,4 +510,6 @@ pub enum WhitelistError {
     TransferNotAllowed,
     #[msg("Whitelist validation is disabled")]
     WhitelistDisabled,
+    #[msg("Token transfers are paused")]
+    TokenPaused,
 }
\ No newline at end of file